@@ -1,9 +1,223 @@
 import { PublicKey, Connection, Transaction, SYSVAR_CLOCK_PUBKEY, SYSVAR_INSTRUCTIONS_PUBKEY, SystemProgram } from '@solana/web3.js';
+import { Program, BN } from '@coral-xyz/anchor';
+import { sha256 } from '@noble/hashes/sha256';
+import type { MoonWalletProgram } from '../../../types/moon_wallet_program';
 import { getWebAuthnAssertion } from './webauthnUtils';
 import { getWalletByCredentialId, getCredentialsByWallet, normalizeCredentialId } from '../firebase/webAuthnService';
 import { addSignerToProposal, updateProposalStatus } from '../firebase/proposalService';
 import { createApproveProposalTx, createExecuteProposalTx } from './transactionUtils';
 
+export const PROGRAM_ID = new PublicKey('6Y3N5AQRQtviTrmRcf29yHWBu3ft7xEoj8kqmykDGvKP');
+
+// Phải khớp với feature cluster mà chương trình được build (payload::CLUSTER_ID)
+export const CLUSTER_IDS = { localnet: 0, devnet: 1, mainnet: 2 } as const;
+export type ClusterId = typeof CLUSTER_IDS[keyof typeof CLUSTER_IDS];
+
+const PAYLOAD_VERSION_V1 = 1;
+const SIGNING_DOMAIN = Buffer.from('moon_wallet:sign');
+
+const u64Le = (value: number | BN): Buffer =>
+  new BN(value.toString()).toArrayLike(Buffer, 'le', 8);
+
+const i64Le = (value: number): Buffer =>
+  new BN(value).toTwos(64).toArrayLike(Buffer, 'le', 8);
+
+/**
+ * Seed của ví mới: SHA256(credential ID || người tạo ví || salt), khớp với `wallet_address_seed`
+ * @param credentialId Credential ID truyền vào initialize_multisig
+ * @param creator Người trả phí khởi tạo ví (fee_payer), trở thành `multisig.owner`
+ * @param salt 32 byte ngẫu nhiên của người tạo ví
+ */
+export const getWalletAddressSeed = (credentialId: string, creator: PublicKey, salt: Uint8Array): Uint8Array =>
+  sha256(Buffer.concat([Buffer.from(credentialId, 'utf8'), creator.toBuffer(), Buffer.from(salt)]));
+
+/**
+ * PDA của ví multisig tạo bởi initialize_multisig (cùng seed với initialize_large_wallet, tiền tố "large_multisig")
+ */
+export const findMultisigPDA = (credentialId: string, creator: PublicKey, salt: Uint8Array): PublicKey =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from('multisig'), Buffer.from(getWalletAddressSeed(credentialId, creator, salt))],
+    PROGRAM_ID
+  )[0];
+
+export const findGuardianPDA = (multisig: PublicKey, guardianId: number | BN): PublicKey =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from('guardian'), multisig.toBuffer(), u64Le(guardianId)],
+    PROGRAM_ID
+  )[0];
+
+export const findProposalPDA = (multisig: PublicKey, proposalId: number | BN): PublicKey =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from('proposal'), multisig.toBuffer(), u64Le(proposalId)],
+    PROGRAM_ID
+  )[0];
+
+/**
+ * ID và PDA của đề xuất tiếp theo: chương trình cấp ID tuần tự từ `multisig.proposal_count`
+ */
+export const getNextProposal = async (
+  program: Program<MoonWalletProgram>,
+  multisig: PublicKey
+): Promise<{ proposalId: BN, proposalPDA: PublicKey }> => {
+  const wallet = await program.account.multiSigWallet.fetch(multisig);
+  return { proposalId: wallet.proposalCount, proposalPDA: findProposalPDA(multisig, wallet.proposalCount) };
+};
+
+/** Hành động được ký, cùng thứ tự biến thể với `payload::SigningAction` */
+export type SigningAction =
+  | { kind: 'approveProposal', proposal: PublicKey, proposalId: number | BN, guardianId: number | BN }
+  | { kind: 'rejectProposal', proposal: PublicKey, proposalId: number | BN, guardianId: number | BN }
+  | { kind: 'createProposal', proposalId: number | BN, contentHash: Uint8Array };
+
+const encodeSigningAction = (action: SigningAction): Buffer => {
+  switch (action.kind) {
+    case 'approveProposal':
+      return Buffer.concat([Buffer.from([2]), action.proposal.toBuffer(), u64Le(action.proposalId), u64Le(action.guardianId)]);
+    case 'rejectProposal':
+      return Buffer.concat([Buffer.from([3]), action.proposal.toBuffer(), u64Le(action.proposalId), u64Le(action.guardianId)]);
+    case 'createProposal':
+      return Buffer.concat([Buffer.from([8]), u64Le(action.proposalId), Buffer.from(action.contentHash)]);
+  }
+};
+
+/**
+ * Payload v1 (Borsh của `payload::SigningPayload`) mà guardian phải ký; challenge WebAuthn là SHA256 của các byte này.
+ * Message dạng chuỗi cũ (`approve:proposal_...`) bị chương trình từ chối.
+ * @param authKey Khóa của guardian (`Guardian.auth`): 33 byte passkey, 32 byte ed25519 hoặc pubkey signer
+ * @param guardianNonce `Guardian.nonce` hiện tại, tăng sau mỗi chữ ký hợp lệ
+ */
+export const buildSigningPayload = (
+  multisig: PublicKey,
+  authKey: Uint8Array,
+  guardianNonce: number | BN,
+  timestamp: number,
+  action: SigningAction,
+  clusterId: ClusterId
+): Buffer =>
+  Buffer.concat([
+    SIGNING_DOMAIN,
+    Buffer.from([PAYLOAD_VERSION_V1]),
+    PROGRAM_ID.toBuffer(),
+    Buffer.from([clusterId]),
+    multisig.toBuffer(),
+    Buffer.from(sha256(authKey)),
+    u64Le(guardianNonce),
+    i64Le(timestamp),
+    encodeSigningAction(action),
+  ]);
+
+/** Khóa dùng trong payload theo loại xác thực của guardian */
+const guardianAuthKey = (auth: any): Uint8Array => {
+  if (!auth) throw new Error('Guardian đã bị thu hồi khóa');
+  if (auth.passkey) return Uint8Array.from(auth.passkey[0]);
+  if (auth.ed25519) return Uint8Array.from(auth.ed25519[0]);
+  return (auth.signer[0] as PublicKey).toBuffer();
+};
+
+/** Nội dung đề xuất, cùng thứ tự với tham số của create_proposal */
+export type ProposalContent = {
+  description: string,
+  action: string,
+  params: any,
+  instructions: any[],
+  actions: any[],
+  expiresAt: BN | null,
+  configAction: any | null,
+};
+
+const encodeString = (value: string): Buffer => {
+  const bytes = Buffer.from(value, 'utf8');
+  const len = Buffer.alloc(4);
+  len.writeUInt32LE(bytes.length);
+  return Buffer.concat([len, bytes]);
+};
+
+const encodeVec = (program: Program<MoonWalletProgram>, typeName: string, items: any[]): Buffer => {
+  const len = Buffer.alloc(4);
+  len.writeUInt32LE(items.length);
+  return Buffer.concat([len, ...items.map(item => program.coder.types.encode(typeName, item))]);
+};
+
+/**
+ * SHA256 của nội dung đề xuất serialize Borsh, nằm trong `SigningAction::CreateProposal`
+ */
+export const getProposalContentHash = (program: Program<MoonWalletProgram>, content: ProposalContent): Uint8Array =>
+  sha256(Buffer.concat([
+    encodeString(content.description),
+    encodeString(content.action),
+    program.coder.types.encode('actionParams', content.params),
+    encodeVec(program, 'proposalInstruction', content.instructions),
+    encodeVec(program, 'proposalAction', content.actions),
+    content.expiresAt === null ? Buffer.from([0]) : Buffer.concat([Buffer.from([1]), i64Le(content.expiresAt.toNumber())]),
+    content.configAction === null
+      ? Buffer.from([0])
+      : Buffer.concat([Buffer.from([1]), program.coder.types.encode('configAction', content.configAction)]),
+  ]));
+
+/**
+ * Chuẩn bị tạo đề xuất: lấy ID tiếp theo từ `multisig.proposal_count` và dựng payload
+ * `CreateProposal` mà guardian đề xuất phải ký.
+ */
+export const prepareCreateProposal = async (
+  program: Program<MoonWalletProgram>,
+  multisig: PublicKey,
+  proposerGuardianId: number,
+  content: ProposalContent,
+  timestamp: number,
+  clusterId: ClusterId
+): Promise<{ proposalId: BN, proposalPDA: PublicKey, guardianPDA: PublicKey, payload: Buffer }> => {
+  const { proposalId, proposalPDA } = await getNextProposal(program, multisig);
+  const guardianPDA = findGuardianPDA(multisig, proposerGuardianId);
+  const guardian = await program.account.guardian.fetch(guardianPDA);
+  const payload = buildSigningPayload(
+    multisig,
+    guardianAuthKey(guardian.auth),
+    guardian.nonce,
+    timestamp,
+    { kind: 'createProposal', proposalId, contentHash: getProposalContentHash(program, content) },
+    clusterId
+  );
+  return { proposalId, proposalPDA, guardianPDA, payload };
+};
+
+/**
+ * Instruction create_proposal; không truyền proposal ID, PDA đề xuất lấy từ `prepareCreateProposal`.
+ * Với passkey, instruction secp256r1 phải được thêm vào cùng transaction.
+ */
+export const createProposalIx = (
+  program: Program<MoonWalletProgram>,
+  multisig: PublicKey,
+  prepared: { proposalPDA: PublicKey, guardianPDA: PublicKey },
+  proposerGuardianId: number,
+  content: ProposalContent,
+  timestamp: number,
+  proof: any,
+  payer: PublicKey
+) =>
+  program.methods
+    .createProposal(
+      content.description,
+      new BN(proposerGuardianId),
+      content.action,
+      content.params,
+      content.instructions,
+      content.actions,
+      content.expiresAt,
+      content.configAction,
+      new BN(timestamp),
+      proof
+    )
+    .accountsStrict({
+      multisig,
+      proposal: prepared.proposalPDA,
+      proposerGuardian: prepared.guardianPDA,
+      payer,
+      instructionSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+      clock: SYSVAR_CLOCK_PUBKEY,
+      systemProgram: SystemProgram.programId,
+    })
+    .instruction();
+
 /**
  * Chuyển đổi credential ID giữa các định dạng
  * @param credentialId ID ban đầu (base64 hoặc hex)
@@ -36,16 +250,67 @@ const getCredentialIdVariations = (credentialId: string): string[] => {
   return Array.from(new Set(variations));
 };
 
+/**
+ * Tìm credential ID của thiết bị này trong localStorage
+ */
+const getStoredCredentialId = (): string | null => {
+  const lastCredentialId = localStorage.getItem("lastCredentialId");
+  if (lastCredentialId) {
+    console.log('Sử dụng lastCredentialId từ localStorage:', lastCredentialId);
+    return lastCredentialId;
+  }
+  
+  try {
+    const userCredentials = JSON.parse(localStorage.getItem("userCredentials") || "[]");
+    if (userCredentials.length > 0) {
+      console.log('Đã tìm thấy credential ID trong localStorage:', userCredentials[0].id);
+      return userCredentials[0].id;
+    }
+  } catch (e) {
+    console.error('Lỗi khi đọc từ localStorage:', e);
+  }
+  
+  try {
+    const webauthnCredentials = JSON.parse(localStorage.getItem("webauthnCredentials") || "[]");
+    if (webauthnCredentials.length > 0) {
+      console.log('Đã tìm thấy credential ID trong webauthnCredentials:', webauthnCredentials[0].credentialId);
+      return webauthnCredentials[0].credentialId;
+    }
+  } catch (e) {
+    console.error('Lỗi khi đọc webauthnCredentials:', e);
+  }
+  
+  try {
+    const publicKeyKeys = Object.keys(localStorage).filter(key => 
+      key.startsWith('guardianPublicKey_'));
+    
+    if (publicKeyKeys.length > 0) {
+      // Lấy credentialId từ tên key
+      const credentialId = publicKeyKeys[0].replace('guardianPublicKey_', '');
+      console.log('Lấy credential ID từ key guardianPublicKey_:', credentialId);
+      return credentialId;
+    }
+  } catch (e) {
+    console.error('Lỗi khi lọc localStorage keys:', e);
+  }
+  
+  return null;
+};
+
 /**
  * Ký một đề xuất
+ * @param program Program Anchor của moon_wallet, dùng để đọc nonce của guardian
  * @param proposalId ID của đề xuất
  * @param multisigAddress Địa chỉ của ví multisig
+ * @param clusterId Cluster mà chương trình được build, nằm trong payload ký
  * @param connection Instance của Connection để gửi transaction
  * @returns Promise<{success: boolean, message: string, signature?: string}>
  */
 export const signProposal = async (
+  program: Program<MoonWalletProgram>,
   proposalId: number,
   multisigAddress: string,
+  clusterId: ClusterId,
   connection: Connection,
   sendTransaction?: (transaction: Transaction, connection: Connection) => Promise<string>
 ): Promise<{success: boolean, message: string, signature?: string}> => {
@@ -57,110 +322,12 @@ export const signProposal = async (
       console.warn("Không có hàm sendTransaction, chỉ cập nhật Firebase mà không gửi transaction lên blockchain");
     }
     
-    // Lấy timestamp hiện tại
-    const timestamp = Math.floor(Date.now() / 1000);
-    
-    // Tạo message template để ký
-    const messageTemplate = `approve:proposal_${proposalId},timestamp:${timestamp}`;
-    console.log('Template message để ký:', messageTemplate);
-    
-    // Yêu cầu người dùng xác thực trực tiếp với WebAuthn - không chỉ định credential ID
-    // allowEmpty = true cho phép người dùng chọn từ danh sách các credential đã đăng ký
-    const assertion = await getWebAuthnAssertion(null, messageTemplate, true);
-    
-    if (!assertion) {
-      return { success: false, message: "Người dùng đã hủy xác thực hoặc xác thực thất bại" };
-    }
-    
-    // Lưu toàn bộ thông tin assertion để debug
-    console.log('Assertion đầy đủ:', assertion);
-    
-    // Ưu tiên sử dụng credentialId từ kết quả assertion (đã thêm trong bản sửa webauthnUtils.ts)
-    let credentialId = assertion.credentialId;
-    
-    // Nếu không có credentialId trong assertion, thử các cách khác
+    // Payload v1 gắn với nonce của guardian nên phải xác định guardian trước khi ký
+    const credentialId = getStoredCredentialId();
     if (!credentialId) {
-      // Cách 1: Lấy từ assertion.credentialId nếu có (tùy thuộc vào cấu trúc response)
-      if (typeof (assertion as any).credentialId === 'string') {
-        credentialId = (assertion as any).credentialId;
-        console.log('Lấy credential ID từ assertion.credentialId:', credentialId);
-      } 
-      // Cách 2: Phân tích từ clientDataJSON
-      else {
-        try {
-          const clientDataObj = JSON.parse(new TextDecoder().decode(assertion.clientDataJSON));
-          if (clientDataObj.credential?.id) {
-            credentialId = clientDataObj.credential.id;
-            console.log('Lấy credential ID từ clientDataJSON:', credentialId);
-          }
-        } catch (e) {
-          console.error('Lỗi khi parse clientDataJSON:', e);
-        }
-      }
-      
-      // Cách 3: Kiểm tra xem có thể lấy từ localStorage không
-      if (!credentialId) {
-        console.log('Tìm kiếm credential ID trong localStorage...');
-        try {
-          const userCredentials = JSON.parse(localStorage.getItem("userCredentials") || "[]");
-          if (userCredentials.length > 0) {
-            credentialId = userCredentials[0].id;
-            console.log('Đã tìm thấy credential ID trong localStorage:', credentialId);
-          }
-        } catch (e) {
-          console.error('Lỗi khi đọc từ localStorage:', e);
-        }
-      }
-      
-      // Cách 4: Kiểm tra webauthnCredentials
-      if (!credentialId) {
-        try {
-          const webauthnCredentials = JSON.parse(localStorage.getItem("webauthnCredentials") || "[]");
-          if (webauthnCredentials.length > 0) {
-            credentialId = webauthnCredentials[0].credentialId;
-            console.log('Đã tìm thấy credential ID trong webauthnCredentials:', credentialId);
-          }
-        } catch (e) {
-          console.error('Lỗi khi đọc webauthnCredentials:', e);
-        }
-      }
-      
-      // Kiểm tra xem cuối cùng đã có credential ID chưa
-      if (!credentialId) {
-        console.error('Không thể lấy credential ID từ bất kỳ nguồn nào');
-        
-        // Giải pháp cuối cùng: Nhập thủ công hoặc sử dụng ID cứng
-        const storedCredentialId = localStorage.getItem("lastCredentialId");
-        if (storedCredentialId) {
-          credentialId = storedCredentialId;
-          console.log('Sử dụng lastCredentialId từ localStorage:', credentialId);
-        } else {
-          // Thử lấy từ publicKey được lưu trong localStorage
-          try {
-            const publicKeyKeys = Object.keys(localStorage).filter(key => 
-              key.startsWith('guardianPublicKey_'));
-            
-            if (publicKeyKeys.length > 0) {
-              // Lấy credentialId từ tên key
-              credentialId = publicKeyKeys[0].replace('guardianPublicKey_', '');
-              console.log('Lấy credential ID từ key guardianPublicKey_:', credentialId);
-            }
-          } catch (e) {
-            console.error('Lỗi khi lọc localStorage keys:', e);
-          }
-        }
-      }
+      return { success: false, message: 'Không tìm thấy credential ID trên thiết bị này. Vui lòng đăng ký lại thiết bị.' };
     }
     
-    if (!credentialId) {
-      return { success: false, message: 'Không nhận được credential ID từ WebAuthn. Vui lòng đăng ký lại thiết bị.' };
-    }
-    
-    console.log('Đã nhận credential ID từ WebAuthn:', credentialId);
-    
-    // Lưu credential ID để sử dụng sau này
-    localStorage.setItem("lastCredentialId", credentialId);
-    
     // Tạo các biến thể khác nhau của credential ID để thử
     const credentialIdVariations = getCredentialIdVariations(credentialId);
     console.log('Các biến thể của credential ID:', credentialIdVariations);
@@ -217,33 +384,36 @@ export const signProposal = async (
     const guardianId = guardianInfo.guardianId;
     console.log('Guardian ID từ Firebase:', guardianId);
     
-    // Lấy WebAuthn public key từ Firebase
-    if (!guardianInfo.guardianPublicKey || guardianInfo.guardianPublicKey.length === 0) {
-      return { success: false, message: 'Không tìm thấy WebAuthn public key trong Firebase' };
-    }
-    
-    // Tính PDA cho multisig và guardian
+    // Tính PDA cho multisig, guardian và proposal
     const multisigPDA = new PublicKey(multisigAddress);
+    const guardianPDA = findGuardianPDA(multisigPDA, guardianId);
+    const proposalPDA = findProposalPDA(multisigPDA, proposalId);
     
-    // Tính PDA cho guardian
-    const [guardianPDA] = await PublicKey.findProgramAddressSync(
-      [
-        Buffer.from('guardian'),
-        multisigPDA.toBuffer(),
-        Buffer.from(new Uint8Array(new BN(guardianId).toArray('le', 8)))
-      ],
-      new PublicKey('6Y3N5AQRQtviTrmRcf29yHWBu3ft7xEoj8kqmykDGvKP')
-    );
+    // Khóa và nonce hiện tại của guardian lấy từ on-chain
+    const guardian = await program.account.guardian.fetch(guardianPDA);
+    
+    // Lấy timestamp hiện tại
+    const timestamp = Math.floor(Date.now() / 1000);
     
-    // Tìm PDA của proposal
-    const [proposalPDA] = await PublicKey.findProgramAddressSync(
-      [
-        Buffer.from('proposal'),
-        multisigPDA.toBuffer(),
-        Buffer.from(new Uint8Array(new BN(proposalId).toArray('le', 8)))
-      ],
-      new PublicKey('6Y3N5AQRQtviTrmRcf29yHWBu3ft7xEoj8kqmykDGvKP')
+    // Payload v1 để ký, challenge WebAuthn là SHA256 của payload
+    const payload = buildSigningPayload(
+      multisigPDA,
+      guardianAuthKey(guardian.auth),
+      guardian.nonce,
+      timestamp,
+      { kind: 'approveProposal', proposal: proposalPDA, proposalId, guardianId },
+      clusterId
     );
+    console.log('Payload để ký:', payload.toString('hex'));
+    
+    const assertion = await getWebAuthnAssertion(credentialId, payload, false);
+    
+    if (!assertion) {
+      return { success: false, message: "Người dùng đã hủy xác thực hoặc xác thực thất bại" };
+    }
+    
+    // Lưu credential ID để sử dụng sau này
+    localStorage.setItem("lastCredentialId", credentialId);
     
     let txSignature: string | undefined;
     
@@ -356,30 +526,3 @@ export const executeProposal = async (
     };
   }
 };
-
-// Thêm BN để xử lý tính toán PDA
-class BN {
-  private value: number;
-  constructor(value: number) {
-    this.value = value;
-  }
-  
-  toArray(endian: 'le' | 'be', length: number): number[] {
-    const result = new Array(length).fill(0);
-    let v = this.value;
-    
-    if (endian === 'le') {
-      for (let i = 0; i < length; i++) {
-        result[i] = v & 0xff;
-        v >>= 8;
-      }
-    } else {
-      for (let i = length - 1; i >= 0; i--) {
-        result[i] = v & 0xff;
-        v >>= 8;
-      }
-    }
-    
-    return result;
-  }
-} 
\ No newline at end of file
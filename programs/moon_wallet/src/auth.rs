@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::WalletError;
+use crate::payload::{signing_message, SigningAction, SigningPayload};
+use crate::precompile::find_ed25519_signature;
+use crate::state::{Guardian, GuardianAuth};
+use crate::webauthn::{verify_webauthn_assertion, WebAuthnAssertion};
+
+/// Bằng chứng guardian gửi kèm khi ký, phải cùng loại với `Guardian::auth`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum GuardianProof {
+    Passkey(WebAuthnAssertion),
+    Ed25519,                         // Chữ ký trên payload v1 nằm trong instruction Ed25519 precompile
+    Signer,                          // Khóa của guardian là signer của giao dịch
+}
+
+/// Thông tin chung của giao dịch dùng để xác thực guardian
+pub struct SigningContext<'a, 'info> {
+    pub instruction_sysvar: &'a AccountInfo<'info>,
+    pub multisig: Pubkey,
+    pub rp_id_hash: [u8; 32],
+    pub signers: Vec<Pubkey>,        // Các tài khoản đã ký giao dịch
+}
+
+/// Lấy danh sách signer của giao dịch: payer và các signer trong remaining_accounts
+pub fn signer_keys(payer: &AccountInfo, remaining_accounts: &[AccountInfo]) -> Vec<Pubkey> {
+    let mut signers: Vec<Pubkey> = remaining_accounts
+        .iter()
+        .filter(|account| account.is_signer)
+        .map(|account| account.key())
+        .collect();
+    if payer.is_signer {
+        signers.push(payer.key());
+    }
+    signers
+}
+
+/// Khóa và bộ đếm của guardian dùng khi xác thực, lấy từ PDA `Guardian` hoặc một ô
+/// trong bảng guardian của `LargeMultiSigWallet`
+pub struct SignerState<'a> {
+    pub guardian_id: u64,
+    pub auth: Option<GuardianAuth>,
+    pub nonce: &'a mut u64,
+    pub sign_count: &'a mut u32,
+}
+
+/// Xác thực guardian theo loại khóa đã đăng ký. Guardian không có khóa thì không thể ký.
+pub fn authenticate_guardian(
+    context: &SigningContext,
+    guardian: &mut Guardian,
+    proof: &GuardianProof,
+    timestamp: i64,
+    action: SigningAction,
+) -> Result<()> {
+    let signer = SignerState {
+        guardian_id: guardian.guardian_id,
+        auth: guardian.auth,
+        nonce: &mut guardian.nonce,
+        sign_count: &mut guardian.sign_count,
+    };
+    authenticate_signer(context, signer, proof, timestamp, action)
+}
+
+pub fn authenticate_signer(
+    context: &SigningContext,
+    signer: SignerState,
+    proof: &GuardianProof,
+    timestamp: i64,
+    action: SigningAction,
+) -> Result<()> {
+    let auth = signer.auth.ok_or(WalletError::GuardianAuthMissing)?;
+    let payload = SigningPayload::new(context.multisig, auth.key_bytes(), *signer.nonce, timestamp, action);
+    
+    match (auth, proof) {
+        (GuardianAuth::Passkey(pubkey), GuardianProof::Passkey(assertion)) => {
+            msg!("Guardian {} xác thực bằng passkey", signer.guardian_id);
+            let expected_message = signing_message(assertion.payload_version, payload)?;
+            verify_webauthn_assertion(
+                context.instruction_sysvar,
+                &pubkey,
+                assertion,
+                &expected_message,
+                &context.rp_id_hash,
+                signer.sign_count,
+            )?;
+        },
+        (GuardianAuth::Ed25519(pubkey), GuardianProof::Ed25519) => {
+            msg!("Guardian {} xác thực bằng khóa ed25519", signer.guardian_id);
+            let message = payload.try_to_vec()?;
+            find_ed25519_signature(context.instruction_sysvar, &pubkey, &message)?;
+        },
+        (GuardianAuth::Signer(key), GuardianProof::Signer) => {
+            msg!("Guardian {} xác thực bằng signer {}", signer.guardian_id, key);
+            require!(context.signers.contains(&key), WalletError::MissingGuardianSignature);
+        },
+        _ => return Err(WalletError::GuardianAuthMismatch.into())
+    }
+    
+    *signer.nonce = signer.nonce.checked_add(1).ok_or(WalletError::ArithmeticOverflow)?;
+    Ok(())
+}
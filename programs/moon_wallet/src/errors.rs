@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::state::proposal::MAX_BATCH_ACTIONS;
 
 
 #[error_code]
@@ -83,4 +84,177 @@ pub enum WalletError {
     
     #[msg("Chủ sở hữu tài khoản không hợp lệ")]
     InvalidOwner,
-}
\ No newline at end of file
+    
+    #[msg("Thiếu tài khoản token cho giao dịch")]
+    TokenAccountMissing,
+    
+    #[msg("Tài khoản token không hợp lệ")]
+    InvalidTokenAccount,
+    
+    #[msg("Token mint không khớp")]
+    TokenMintMismatch,
+    
+    #[msg("Thiếu tài khoản cho instruction CPI")]
+    CpiAccountMissing,
+    
+    #[msg("Không được phép gọi CPI tới program này")]
+    InvalidCpiProgram,
+    
+    #[msg("Bước trong batch không hợp lệ")]
+    InvalidBatchStep,
+    
+    // Bước batch thất bại: mã lỗi = mã của BatchStep0Failed + chỉ số bước, xem `WalletError::batch_step_failed`
+    #[msg("Bước 0 của batch thực thi thất bại")]
+    BatchStep0Failed,
+    
+    #[msg("Bước 1 của batch thực thi thất bại")]
+    BatchStep1Failed,
+    
+    #[msg("Bước 2 của batch thực thi thất bại")]
+    BatchStep2Failed,
+    
+    #[msg("Bước 3 của batch thực thi thất bại")]
+    BatchStep3Failed,
+    
+    #[msg("Bước 4 của batch thực thi thất bại")]
+    BatchStep4Failed,
+    
+    #[msg("Bước 5 của batch thực thi thất bại")]
+    BatchStep5Failed,
+    
+    #[msg("Bước 6 của batch thực thi thất bại")]
+    BatchStep6Failed,
+    
+    #[msg("Bước 7 của batch thực thi thất bại")]
+    BatchStep7Failed,
+    
+    #[msg("Bước 8 của batch thực thi thất bại")]
+    BatchStep8Failed,
+    
+    #[msg("Bước 9 của batch thực thi thất bại")]
+    BatchStep9Failed,
+    
+    #[msg("Đề xuất đã hết hạn")]
+    ProposalExpired,
+    
+    #[msg("Thời điểm hết hạn không hợp lệ")]
+    InvalidExpiry,
+    
+    #[msg("Đề xuất chưa kết thúc, không thể đóng")]
+    ProposalNotFinalized,
+    
+    #[msg("Guardian đã bỏ phiếu này cho đề xuất")]
+    AlreadyVoted,
+    
+    #[msg("Cấu hình timelock không hợp lệ")]
+    InvalidTimelock,
+    
+    #[msg("Đề xuất vẫn đang trong thời gian khóa")]
+    TimelockActive,
+    
+    #[msg("Vượt hạn mức chi tiêu, cần tạo đề xuất multisig")]
+    SpendingLimitExceeded,
+    
+    #[msg("Địa chỉ đích không nằm trong danh sách cho phép")]
+    DestinationNotAllowed,
+    
+    #[msg("Thay đổi guardian phải được thực hiện qua đề xuất multisig")]
+    GuardianChangeRequiresProposal,
+    
+    #[msg("Ví phải còn ít nhất một owner đang hoạt động")]
+    NoActiveOwner,
+    
+    #[msg("Yêu cầu khôi phục không còn hiệu lực")]
+    StaleRecoveryRequest,
+    
+    #[msg("Yêu cầu khôi phục vẫn đang trong thời gian thử thách")]
+    RecoveryDelayActive,
+    
+    #[msg("Đã hết thời gian thử thách, không thể hủy yêu cầu khôi phục")]
+    RecoveryChallengeEnded,
+    
+    #[msg("Chưa đủ guardian phê duyệt yêu cầu khôi phục")]
+    InsufficientRecoveryApprovals,
+    
+    #[msg("Yêu cầu khôi phục chưa kết thúc, không thể đóng")]
+    RecoveryNotFinalized,
+    
+    #[msg("Ví không còn guardian nào có thể phê duyệt khôi phục")]
+    NoRecoveryApprovers,
+    
+    #[msg("Yêu cầu khôi phục đã hết hạn")]
+    RecoveryRequestExpired,
+    
+    #[msg("authenticatorData không hợp lệ")]
+    InvalidAuthenticatorData,
+    
+    #[msg("clientDataJSON không hợp lệ")]
+    InvalidClientData,
+    
+    #[msg("Challenge trong clientDataJSON không khớp với hành động")]
+    ChallengeMismatch,
+    
+    #[msg("rpIdHash không khớp với ví")]
+    RpIdMismatch,
+    
+    #[msg("Người dùng chưa được xác minh (cờ UP/UV)")]
+    UserNotVerified,
+    
+    #[msg("Bộ đếm chữ ký không tăng, có thể là chữ ký bị phát lại")]
+    SignCountReplay,
+    
+    #[msg("Instruction secp256r1 tham chiếu dữ liệu ngoài chính nó")]
+    InvalidSecpInstructionIndex,
+    
+    #[msg("Phiên bản payload ký không được hỗ trợ")]
+    UnsupportedPayloadVersion,
+    
+    #[msg("Guardian chưa có khóa xác thực")]
+    GuardianAuthMissing,
+    
+    #[msg("Loại bằng chứng không khớp với loại khóa của guardian")]
+    GuardianAuthMismatch,
+    
+    #[msg("Guardian chưa ký giao dịch")]
+    MissingGuardianSignature,
+    
+    #[msg("Thiết bị không thuộc owner hoặc đã bị thu hồi")]
+    InvalidDevice,
+    
+    #[msg("Guardian vẫn còn thiết bị passkey, cần gỡ thiết bị trước")]
+    DevicesStillRegistered,
+    
+    #[msg("Guardian không có quyền thực hiện thao tác này")]
+    PermissionDenied,
+    
+    #[msg("Ví còn đề xuất đang chờ, cần thực thi, từ chối hoặc hủy trước khi chuyển")]
+    PendingProposalsExist,
+    
+    #[msg("Ví đang có yêu cầu khôi phục chưa kết thúc")]
+    RecoveryInProgress,
+    
+    #[msg("Tài khoản đã ở layout mới nhất")]
+    AccountAlreadyMigrated,
+    
+    #[msg("Ví phải được chuyển lên layout mới trước các đề xuất của nó")]
+    WalletNotMigrated,
+}
+
+impl WalletError {
+    /// Lỗi ứng với bước thứ `index` (tính từ 0) của đề xuất "batch"
+    pub fn batch_step_failed(index: usize) -> Self {
+        const STEPS: [WalletError; MAX_BATCH_ACTIONS] = [
+            WalletError::BatchStep0Failed,
+            WalletError::BatchStep1Failed,
+            WalletError::BatchStep2Failed,
+            WalletError::BatchStep3Failed,
+            WalletError::BatchStep4Failed,
+            WalletError::BatchStep5Failed,
+            WalletError::BatchStep6Failed,
+            WalletError::BatchStep7Failed,
+            WalletError::BatchStep8Failed,
+            WalletError::BatchStep9Failed,
+        ];
+        STEPS[index]
+    }
+}
@@ -0,0 +1,266 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+use crate::instructions::wallet::multisig_address_seed;
+use crate::instructions::recovery::check_timestamp;
+use crate::payload::{signing_message, SigningAction, SigningPayload};
+use crate::webauthn::{verify_webauthn_assertion, WebAuthnAssertion};
+use anchor_lang::solana_program::hash::hash;
+
+#[derive(Accounts)]
+#[instruction(credential_id: String)]
+pub struct AddDevice<'info> {
+    #[account(
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultiSigWallet>,
+    
+    #[account(
+        mut,
+        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &guardian.guardian_id.to_le_bytes()],
+        bump = guardian.bump,
+        constraint = guardian.is_owner @ WalletError::InvalidGuardian,
+        constraint = guardian.is_active @ WalletError::InactiveGuardian
+    )]
+    pub guardian: Account<'info, Guardian>,
+    
+    /// Thiết bị đã đăng ký ký thao tác này. Bỏ trống: passkey chính của owner ký.
+    #[account(
+        mut,
+        constraint = signer_device.guardian == guardian.key() @ WalletError::InvalidDevice
+    )]
+    pub signer_device: Option<Account<'info, OwnerDevice>>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + OwnerDevice::INIT_SPACE,
+        seeds = [b"device".as_ref(), guardian.key().as_ref(), &hash(credential_id.as_bytes()).to_bytes()],
+        bump
+    )]
+    pub device: Account<'info, OwnerDevice>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: Đây là tài khoản sysvar chứa thông tin về các instruction trong transaction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+    
+    pub clock: Sysvar<'info, Clock>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Đăng ký thêm một passkey cho owner. Thao tác phải được ký bằng passkey chính
+/// hoặc một thiết bị đã đăng ký của chính owner đó.
+pub fn add_device(
+    ctx: Context<AddDevice>,
+    credential_id: String,
+    pubkey: [u8; 33],
+    label: String,
+    timestamp: i64,
+    assertion: WebAuthnAssertion
+) -> Result<()> {
+    require!(
+        !credential_id.is_empty() && credential_id.len() <= MAX_DEVICE_CREDENTIAL_ID_LEN,
+        WalletError::InvalidOperation
+    );
+    require!(label.len() <= MAX_DEVICE_LABEL_LEN, WalletError::NameTooLong);
+    require!(
+        ctx.accounts.guardian.device_count < MAX_OWNER_DEVICES,
+        WalletError::LimitExceeded
+    );
+    
+    check_timestamp(timestamp, &ctx.accounts.clock)?;
+    
+    let credential_hash = hash(credential_id.as_bytes()).to_bytes();
+    authorize_device_change(
+        &ctx.accounts.instruction_sysvar,
+        &ctx.accounts.multisig,
+        &mut ctx.accounts.guardian,
+        ctx.accounts.signer_device.as_mut(),
+        &assertion,
+        timestamp,
+        SigningAction::AddDevice { credential_hash, pubkey },
+    )?;
+    
+    let guardian = &mut ctx.accounts.guardian;
+    let device = &mut ctx.accounts.device;
+    device.version = ACCOUNT_VERSION;
+    device.guardian = guardian.key();
+    device.credential_id = credential_id;
+    device.credential_hash = credential_hash;
+    device.pubkey = pubkey;
+    device.label = label;
+    device.created_at = ctx.accounts.clock.unix_timestamp;
+    device.sign_count = 0;
+    device.epoch = guardian.device_epoch;
+    device.bump = ctx.bumps.device;
+    
+    guardian.device_count += 1;
+    
+    msg!("Đã thêm thiết bị \"{}\" cho owner {}", device.label, guardian.guardian_id);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemoveDevice<'info> {
+    #[account(
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultiSigWallet>,
+    
+    #[account(
+        mut,
+        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &guardian.guardian_id.to_le_bytes()],
+        bump = guardian.bump,
+        constraint = guardian.is_owner @ WalletError::InvalidGuardian,
+        constraint = guardian.is_active @ WalletError::InactiveGuardian
+    )]
+    pub guardian: Account<'info, Guardian>,
+    
+    /// Thiết bị đã đăng ký ký thao tác này, phải khác thiết bị bị gỡ.
+    /// Bỏ trống: passkey chính của owner ký.
+    #[account(
+        mut,
+        constraint = signer_device.guardian == guardian.key() @ WalletError::InvalidDevice
+    )]
+    pub signer_device: Option<Account<'info, OwnerDevice>>,
+    
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"device".as_ref(), guardian.key().as_ref(), &device.credential_hash],
+        bump = device.bump,
+        constraint = device.guardian == guardian.key() @ WalletError::InvalidDevice
+    )]
+    pub device: Account<'info, OwnerDevice>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: Đây là tài khoản sysvar chứa thông tin về các instruction trong transaction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+    
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Gỡ một passkey của owner, rent được trả cho payer
+pub fn remove_device(
+    ctx: Context<RemoveDevice>,
+    timestamp: i64,
+    assertion: WebAuthnAssertion
+) -> Result<()> {
+    let device_key = ctx.accounts.device.key();
+    // Tránh hai tài khoản trùng nhau: thiết bị bị đóng không thể đồng thời ghi signCount
+    if let Some(signer_device) = &ctx.accounts.signer_device {
+        require!(signer_device.key() != device_key, WalletError::InvalidDevice);
+    }
+    
+    check_timestamp(timestamp, &ctx.accounts.clock)?;
+    
+    authorize_device_change(
+        &ctx.accounts.instruction_sysvar,
+        &ctx.accounts.multisig,
+        &mut ctx.accounts.guardian,
+        ctx.accounts.signer_device.as_mut(),
+        &assertion,
+        timestamp,
+        SigningAction::RemoveDevice { credential_hash: ctx.accounts.device.credential_hash },
+    )?;
+    
+    let guardian = &mut ctx.accounts.guardian;
+    guardian.device_count -= 1;
+    
+    msg!("Đã gỡ thiết bị \"{}\" của owner {}", ctx.accounts.device.label, guardian.guardian_id);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseRevokedDevice<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultiSigWallet>,
+    
+    #[account(
+        mut,
+        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &guardian.guardian_id.to_le_bytes()],
+        bump = guardian.bump
+    )]
+    pub guardian: Account<'info, Guardian>,
+    
+    #[account(
+        mut,
+        close = multisig,
+        seeds = [b"device".as_ref(), guardian.key().as_ref(), &device.credential_hash],
+        bump = device.bump,
+        constraint = device.guardian == guardian.key() @ WalletError::InvalidDevice,
+        constraint = device.epoch != guardian.device_epoch @ WalletError::InvalidDevice
+    )]
+    pub device: Account<'info, OwnerDevice>,
+}
+
+/// Đóng thiết bị đã bị thu hồi (sau khôi phục hoặc khi mất quyền owner).
+/// Ai cũng có thể gọi, rent được trả về ví multisig.
+pub fn close_revoked_device(ctx: Context<CloseRevokedDevice>) -> Result<()> {
+    let guardian = &mut ctx.accounts.guardian;
+    guardian.device_count -= 1;
+    
+    msg!("Đã đóng thiết bị bị thu hồi \"{}\" của guardian {}", ctx.accounts.device.label, guardian.guardian_id);
+    
+    Ok(())
+}
+
+/// Public key passkey dùng để ký: của thiết bị bổ sung nếu có, nếu không thì passkey chính của owner
+pub fn owner_passkey(guardian: &Account<Guardian>, device: Option<&OwnerDevice>) -> Result<[u8; 33]> {
+    match device {
+        Some(device) => {
+            require!(
+                device.guardian == guardian.key() && device.epoch == guardian.device_epoch,
+                WalletError::InvalidDevice
+            );
+            msg!("Owner {} ký bằng thiết bị \"{}\"", guardian.guardian_id, device.label);
+            Ok(device.pubkey)
+        },
+        None => Ok(guardian.passkey().ok_or(WalletError::WebAuthnNotConfigured)?),
+    }
+}
+
+/// Thêm/gỡ thiết bị chỉ chấp nhận payload v1, ký bằng passkey chính hoặc thiết bị đã đăng ký
+fn authorize_device_change(
+    instruction_sysvar: &AccountInfo,
+    multisig: &Account<MultiSigWallet>,
+    guardian: &mut Account<Guardian>,
+    signer_device: Option<&mut Account<OwnerDevice>>,
+    assertion: &WebAuthnAssertion,
+    timestamp: i64,
+    action: SigningAction,
+) -> Result<()> {
+    let webauthn_pubkey = owner_passkey(guardian, signer_device.as_deref().map(|device| &**device))?;
+    let payload = SigningPayload::new(multisig.key(), &webauthn_pubkey, guardian.nonce, timestamp, action);
+    let expected_message = signing_message(assertion.payload_version, payload)?;
+    
+    let sign_count = match signer_device {
+        Some(device) => &mut device.sign_count,
+        None => &mut guardian.sign_count,
+    };
+    verify_webauthn_assertion(
+        instruction_sysvar,
+        &webauthn_pubkey,
+        assertion,
+        &expected_message,
+        &multisig.rp_id_hash,
+        sign_count,
+    )?;
+    
+    guardian.consume_nonce()
+}
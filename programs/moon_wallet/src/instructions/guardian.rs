@@ -1,34 +1,31 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Allocate, Assign, CreateAccount, Transfer};
 use crate::state::*;
 use crate::errors::*;
 use anchor_lang::solana_program::hash::hash;
 
 
-use crate::instructions::wallet::process_credential_id_seed;
+use crate::instructions::wallet::multisig_address_seed;
 
 
+/// Chỉ dùng khi khởi tạo ví: người tạo ví thêm guardian trực tiếp cho đến khi đề xuất đầu tiên được tạo.
+/// Mỗi lần thêm, ví phải thỏa bất biến, nên ví bắt đầu với ngưỡng mà guardian đầu tiên đáp ứng được
+/// và nâng ngưỡng bằng đề xuất `ChangeThreshold`. Sau đó mọi thay đổi guardian phải đi qua đề xuất `ConfigAction`.
 #[derive(Accounts)]
 #[instruction(guardian_id: u64)]
 pub struct AddGuardian<'info> {
     #[account(
         mut,
-        seeds = [b"multisig".as_ref(), &process_credential_id_seed(&multisig.credential_id)],
-        bump = multisig.bump
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump,
+        constraint = multisig.proposal_count == 0 @ WalletError::GuardianChangeRequiresProposal
     )]
     pub multisig: Account<'info, MultiSigWallet>,
     
     #[account(
         init,
         payer = payer,
-        space = 8 + 
-               32 +  
-               8 + 
-               4 + 32 + 
-               1 +  
-               32 +  
-               1 +   
-               1 + 33 + 
-               1,    
+        space = 8 + Guardian::INIT_SPACE,
         seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &guardian_id.to_le_bytes()],
         bump
     )]
@@ -37,49 +34,64 @@ pub struct AddGuardian<'info> {
     /// CHECK: Không còn cần thiết nhưng giữ lại để tương thích
     pub guardian_pubkey: AccountInfo<'info>,
     
-    #[account(mut)]
+    #[account(mut, address = multisig.owner @ WalletError::InvalidOwner)]
     pub payer: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn add_guardian(
     ctx: Context<AddGuardian>,
     guardian_id: u64,
     guardian_name: String,
     recovery_hash_intermediate: [u8; 32],
     is_owner: bool,
-    webauthn_pubkey: Option<[u8; 33]>,
+    auth: GuardianAuth,
+    permissions: u8,
+    weight: Option<u8>,
 ) -> Result<()> {
     let multisig = &mut ctx.accounts.multisig;
     let guardian = &mut ctx.accounts.guardian;
     
-    require!(multisig.guardian_count < 8, WalletError::LimitExceeded);
-    require!(guardian_name.len() <= 32, WalletError::NameTooLong);
-    
+    require!(multisig.guardian_count < MAX_GUARDIANS, WalletError::LimitExceeded);
+    require!(guardian_name.len() <= MAX_GUARDIAN_NAME_LEN, WalletError::NameTooLong);
+    validate_permissions(permissions)?;
+    let weight = weight.unwrap_or(1);
+    validate_weight(weight)?;
     
     if is_owner {
-    
-    }
-    
-    
-    if is_owner {
-        require!(webauthn_pubkey.is_some(), WalletError::WebAuthnNotConfigured);
+        require!(matches!(auth, GuardianAuth::Passkey(_)), WalletError::WebAuthnNotConfigured);
     }
     
     let hash_result = hash(&recovery_hash_intermediate);
     let final_hash: [u8; 32] = hash_result.to_bytes();
 
+    guardian.version = ACCOUNT_VERSION;
     guardian.wallet = multisig.key();
     guardian.guardian_id = guardian_id;
     guardian.name = guardian_name;
     guardian.is_active = true;
     guardian.recovery_hash = final_hash; 
     guardian.is_owner = is_owner;
-    guardian.webauthn_pubkey = webauthn_pubkey;
+    guardian.auth = Some(auth);
     guardian.bump = ctx.bumps.guardian;
+    guardian.sign_count = 0;
+    guardian.nonce = 0;
+    guardian.device_count = 0;
+    guardian.device_epoch = 0;
+    guardian.permissions = permissions;
+    guardian.spending_limits = Vec::new();
+    guardian.weight = weight;
 
     multisig.guardian_count += 1;
+    multisig.active_guardian_count += 1;
+    if is_owner {
+        multisig.owner_count += 1;
+    }
+    multisig.update_approvers(0, guardian.approver_weight());
+    multisig.sync_guardian_entry(guardian)?;
+    multisig.check_invariants()?;
     
     msg!("Guardian đã được thêm thành công");
     if is_owner {
@@ -89,168 +101,155 @@ pub fn add_guardian(
     Ok(())
 }
 
-#[derive(Accounts)]
-#[instruction(guardian_id: u64, owner_guardian_id: u64)]
-pub struct RemoveGuardian<'info> {
-    #[account(
-        mut,
-        seeds = [b"multisig".as_ref(), &process_credential_id_seed(&multisig.credential_id)],
-        bump = multisig.bump
-    )]
-    pub multisig: Account<'info, MultiSigWallet>,
-    
-    #[account(
-        mut,
-        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &guardian_id.to_le_bytes()],
-        bump = guardian.bump,
-        close = owner
-    )]
-    pub guardian: Account<'info, Guardian>,
-    
-    /// CHECK: Không còn cần thiết nhưng giữ lại để tương thích
-    pub guardian_pubkey: AccountInfo<'info>,
-    
-   
-    #[account(
-        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &owner_guardian_id.to_le_bytes()],
-        constraint = owner_guardian.is_owner == true,
-        bump = owner_guardian.bump
-    )]
-    pub owner_guardian: Account<'info, Guardian>,
-    
-    #[account(mut)]
-    pub owner: Signer<'info>,
+/// Tạo PDA guardian mới khi thực thi đề xuất AddGuardian. PDA phải được truyền
+/// qua remaining_accounts (writable), `payer` trả rent.
+#[allow(clippy::too_many_arguments)]
+pub fn create_guardian_account<'info>(
+    multisig: &mut Account<'info, MultiSigWallet>,
+    guardian_info: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    guardian_id: u64,
+    name: String,
+    recovery_hash: [u8; 32],
+    is_owner: bool,
+    auth: GuardianAuth,
+    permissions: u8,
+    weight: u8,
+) -> Result<()> {
+    require!(multisig.guardian_count < MAX_GUARDIANS, WalletError::LimitExceeded);
+    validate_weight(weight)?;
+    
+    let multisig_key = multisig.key();
+    let guardian_id_bytes = guardian_id.to_le_bytes();
+    let (guardian_pda, bump) = Pubkey::find_program_address(
+        &[b"guardian".as_ref(), multisig_key.as_ref(), &guardian_id_bytes],
+        &crate::ID,
+    );
+    require!(guardian_info.key() == guardian_pda, WalletError::InvalidGuardian);
+    require!(guardian_info.data_is_empty(), WalletError::InvalidGuardian);
+    
+    let seeds = &[
+        b"guardian".as_ref(),
+        multisig_key.as_ref(),
+        &guardian_id_bytes,
+        &[bump]
+    ];
+    let signer_seeds = &[&seeds[..]];
+    
+    create_program_account(payer, guardian_info, system_program, 8 + Guardian::INIT_SPACE, signer_seeds)?;
+    
+    let guardian = Guardian {
+        version: ACCOUNT_VERSION,
+        wallet: multisig_key,
+        guardian_id,
+        name,
+        is_active: true,
+        recovery_hash,
+        is_owner,
+        auth: Some(auth),
+        bump,
+        sign_count: 0,
+        nonce: 0,
+        device_count: 0,
+        device_epoch: 0,
+        permissions,
+        spending_limits: Vec::new(),
+        weight,
+        reserved: [0; GUARDIAN_RESERVED_LEN],
+    };
+    let mut data = guardian_info.try_borrow_mut_data()?;
+    guardian.try_serialize(&mut &mut data[..])?;
     
-    pub system_program: Program<'info, System>,
-}
-
-pub fn remove_guardian(ctx: Context<RemoveGuardian>) -> Result<()> {
-    let multisig = &mut ctx.accounts.multisig;
+    multisig.guardian_count += 1;
+    multisig.active_guardian_count += 1;
+    if is_owner {
+        multisig.owner_count += 1;
+    }
+    multisig.update_approvers(0, guardian.approver_weight());
+    multisig.sync_guardian_entry(&guardian)?;
     
-   
-    require!(multisig.guardian_count > 0, WalletError::GuardianError);
-    multisig.guardian_count -= 1;
-
-    msg!("Guardian đã được xóa");
+    msg!("Guardian {} đã được thêm qua đề xuất", guardian_id);
     Ok(())
 }
 
-// Thêm context cho việc cập nhật trạng thái guardian
-#[derive(Accounts)]
-#[instruction(guardian_id: u64, owner_guardian_id: u64, is_active: bool)]
-pub struct UpdateGuardianStatus<'info> {
-    #[account(
-        mut,
-        seeds = [b"multisig".as_ref(), &process_credential_id_seed(&multisig.credential_id)],
-        bump = multisig.bump
-    )]
-    pub multisig: Account<'info, MultiSigWallet>,
-    
-    #[account(
-        mut,
-        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &guardian_id.to_le_bytes()],
-        bump = guardian.bump
-    )]
-    pub guardian: Account<'info, Guardian>,
-    
-    /// CHECK: Không còn cần thiết nhưng giữ lại để tương thích
-    pub guardian_pubkey: AccountInfo<'info>,
-    
-    /// Tài khoản guardian của người gọi, phải là owner
-    #[account(
-        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &owner_guardian_id.to_le_bytes()],
-        constraint = owner_guardian.is_owner == true,
-        bump = owner_guardian.bump
-    )]
-    pub owner_guardian: Account<'info, Guardian>,
-    
-    #[account(mut)]
-    pub owner: Signer<'info>,
-}
-
-
-pub fn update_guardian_status(
-    ctx: Context<UpdateGuardianStatus>,
-    is_active: bool,
+/// Tạo PDA thuộc chương trình với `space` byte, ký bằng `signer_seeds` của chính PDA đó
+pub fn create_program_account<'info>(
+    payer: &Signer<'info>,
+    account_info: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    space: usize,
+    signer_seeds: &[&[&[u8]]],
 ) -> Result<()> {
-    let guardian = &mut ctx.accounts.guardian;
-    
-  
-    require!(ctx.accounts.owner_guardian.is_owner, WalletError::InvalidOperation);
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = account_info.lamports();
+    
+    // Giống `init` của Anchor: nếu PDA đã có lamports thì chỉ nạp thêm, cấp phát và gán owner
+    if current_lamports == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                CreateAccount {
+                    from: payer.to_account_info(),
+                    to: account_info.clone(),
+                },
+                signer_seeds,
+            ),
+            required_lamports,
+            space as u64,
+            &crate::ID,
+        )?;
+    } else {
+        let top_up = required_lamports.saturating_sub(current_lamports);
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    Transfer {
+                        from: payer.to_account_info(),
+                        to: account_info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                Allocate { account_to_allocate: account_info.clone() },
+                signer_seeds,
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                Assign { account_to_assign: account_info.clone() },
+                signer_seeds,
+            ),
+            &crate::ID,
+        )?;
+    }
     
-    // Cập nhật trạng thái
-    guardian.is_active = is_active;
-
-    msg!("Trạng thái guardian đã được cập nhật");
     Ok(())
 }
 
-#[derive(Accounts)]
-#[instruction(old_guardian_id: u64, new_guardian_id: u64, recovery_hash_intermediate: [u8; 32])]
-pub struct RecoverAccessByGuardian<'info> {
-    #[account(
-        mut,
-        seeds = [b"multisig".as_ref(), &process_credential_id_seed(&multisig.credential_id)],
-        bump = multisig.bump
-    )]
-    pub multisig: Account<'info, MultiSigWallet>,
-    
-
-    #[account(
-        mut,
-        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &old_guardian_id.to_le_bytes()],
-        bump = old_guardian.bump,
-        constraint = old_guardian.is_owner == true
-    )]
-    pub old_guardian: Account<'info, Guardian>,
-    
-    /// CHECK: Không còn cần thiết nhưng giữ lại để tương thích
-    pub old_guardian_pubkey: AccountInfo<'info>,
-    
-
-    #[account(
-        mut,
-        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &new_guardian_id.to_le_bytes()],
-        bump = new_guardian.bump
-    )]
-    pub new_guardian: Account<'info, Guardian>,
-    
-    /// CHECK: Không còn cần thiết nhưng giữ lại để tương thích
-    pub new_guardian_pubkey: AccountInfo<'info>,
-    
-    pub system_program: Program<'info, System>,
+/// Tải guardian của ví từ remaining_accounts theo guardian_id
+pub fn load_guardian<'info>(
+    multisig_key: &Pubkey,
+    guardian_id: u64,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<Account<'info, Guardian>> {
+    let (guardian_pda, _) = Pubkey::find_program_address(
+        &[b"guardian".as_ref(), multisig_key.as_ref(), &guardian_id.to_le_bytes()],
+        &crate::ID,
+    );
+    let guardian_info = remaining_accounts
+        .iter()
+        .find(|account| *account.key == guardian_pda)
+        .ok_or(WalletError::InvalidGuardian)?;
+    
+    let guardian = Account::<Guardian>::try_from(guardian_info)?;
+    require!(guardian.wallet == *multisig_key, WalletError::InvalidGuardian);
+    Ok(guardian)
 }
-
-
-pub fn recover_access_by_guardian(
-    ctx: Context<RecoverAccessByGuardian>,
-    recovery_hash_intermediate: [u8; 32],
-    new_webauthn_pubkey: [u8; 33],
-) -> Result<()> {
-    let multisig = &mut ctx.accounts.multisig;
-    let old_guardian = &mut ctx.accounts.old_guardian;
-    let new_guardian = &mut ctx.accounts.new_guardian;
-    
-
-    require!(old_guardian.wallet == multisig.key(), WalletError::InvalidGuardian);
-    require!(old_guardian.is_active, WalletError::InactiveGuardian);
-    
-  
-    let hash_result = hash(&recovery_hash_intermediate);
-    let final_hash: [u8; 32] = hash_result.to_bytes();
-    
-    require!(old_guardian.recovery_hash == final_hash, WalletError::InvalidRecoveryKey);
-    
-    
-    old_guardian.is_owner = false;
-    old_guardian.webauthn_pubkey = None;
-    
-    new_guardian.is_owner = true;
-    new_guardian.webauthn_pubkey = Some(new_webauthn_pubkey);
-    
-
-    multisig.recovery_nonce += 1;
-    
-    msg!("Quyền truy cập đã được khôi phục thành công thông qua guardian");
-    Ok(())
-}
\ No newline at end of file
@@ -0,0 +1,354 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+use crate::instructions::wallet::wallet_address_seed;
+use crate::auth::{authenticate_signer, signer_keys, GuardianProof, SignerState, SigningContext};
+use crate::payload::SigningAction;
+use anchor_lang::solana_program::hash::hash;
+
+#[derive(Accounts)]
+#[instruction(threshold: u8, credential_id: String, rp_id: String, salt: [u8; 32])]
+pub struct InitializeLargeWallet<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = fee_payer,
+        space = 8 + std::mem::size_of::<LargeMultiSigWallet>(),
+        seeds = [b"large_multisig".as_ref(), &wallet_address_seed(&credential_id, &fee_payer.key(), &salt)],
+        bump
+    )]
+    pub wallet: AccountLoader<'info, LargeMultiSigWallet>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Tạo ví zero-copy. Bảng guardian được điền bằng `add_large_guardian` trước khi tạo đề xuất đầu tiên.
+pub fn initialize_large_wallet(
+    ctx: Context<InitializeLargeWallet>,
+    threshold: u8,
+    credential_id: String,
+    rp_id: String,
+    salt: [u8; 32],
+) -> Result<()> {
+    require!(threshold > 0, WalletError::InvalidConfig);
+    require!(threshold as usize <= MAX_LARGE_GUARDIANS, WalletError::InvalidThreshold);
+    require!(!credential_id.is_empty(), WalletError::InvalidConfig);
+    require!(credential_id.len() <= MAX_CREDENTIAL_ID_LEN, WalletError::NameTooLong);
+    require!(!rp_id.is_empty(), WalletError::InvalidConfig);
+    
+    let mut wallet = ctx.accounts.wallet.load_init()?;
+    wallet.version = ACCOUNT_VERSION;
+    wallet.bump = ctx.bumps.wallet;
+    wallet.threshold = threshold;
+    wallet.owner = ctx.accounts.fee_payer.key();
+    wallet.rp_id_hash = hash(rp_id.as_bytes()).to_bytes();
+    wallet.address_seed = wallet_address_seed(&credential_id, &ctx.accounts.fee_payer.key(), &salt);
+    
+    msg!("Đã tạo ví zero-copy với ngưỡng {}", threshold);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AddLargeGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [b"large_multisig".as_ref(), &wallet.load()?.address_seed],
+        bump = wallet.load()?.bump,
+        constraint = wallet.load()?.proposal_count == 0 @ WalletError::GuardianChangeRequiresProposal
+    )]
+    pub wallet: AccountLoader<'info, LargeMultiSigWallet>,
+    
+    #[account(address = wallet.load()?.owner @ WalletError::InvalidOwner)]
+    pub owner: Signer<'info>,
+}
+
+/// Chỉ dùng trong giai đoạn thiết lập: owner thêm guardian trực tiếp cho đến khi ví có đề xuất đầu tiên
+pub fn add_large_guardian(
+    ctx: Context<AddLargeGuardian>,
+    guardian_id: u64,
+    is_owner: bool,
+    auth: GuardianAuth,
+    permissions: u8,
+) -> Result<()> {
+    validate_permissions(permissions)?;
+    if is_owner {
+        require!(matches!(auth, GuardianAuth::Passkey(_)), WalletError::WebAuthnNotConfigured);
+    }
+    
+    let mut wallet = ctx.accounts.wallet.load_mut()?;
+    let index = wallet.guardian_count as usize;
+    require!(index < MAX_LARGE_GUARDIANS, WalletError::LimitExceeded);
+    require!(
+        wallet.slots().iter().all(|slot| slot.guardian_id != guardian_id),
+        WalletError::InvalidGuardian
+    );
+    
+    let slot = &mut wallet.guardians[index];
+    slot.guardian_id = guardian_id;
+    slot.nonce = 0;
+    slot.sign_count = 0;
+    slot.permissions = permissions;
+    slot.flags = SLOT_FLAG_ACTIVE | if is_owner { SLOT_FLAG_OWNER } else { 0 };
+    slot.set_auth(auth);
+    let is_approver = slot.is_approver();
+    
+    wallet.guardian_count += 1;
+    wallet.active_guardian_count += 1;
+    if is_owner {
+        wallet.owner_count += 1;
+    }
+    if is_approver {
+        wallet.approver_count += 1;
+    }
+    
+    msg!("Guardian {} đã được thêm vào ô {}", guardian_id, index);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateLargeProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"large_multisig".as_ref(), &wallet.load()?.address_seed],
+        bump = wallet.load()?.bump
+    )]
+    pub wallet: AccountLoader<'info, LargeMultiSigWallet>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<LargeProposal>(),
+        seeds = [
+            b"large_proposal".as_ref(),
+            wallet.key().as_ref(),
+            &wallet.load()?.proposal_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub proposal: AccountLoader<'info, LargeProposal>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: Đây là tài khoản sysvar chứa thông tin về các instruction trong transaction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+    
+    pub clock: Sysvar<'info, Clock>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Tạo đề xuất chuyển SOL. ID được cấp tuần tự từ `proposal_count`; guardian đề xuất ký
+/// `SigningAction::CreateProposal` với nội dung (amount, destination).
+pub fn create_large_proposal(
+    ctx: Context<CreateLargeProposal>,
+    proposer_guardian_id: u64,
+    amount: u64,
+    destination: Pubkey,
+    timestamp: i64,
+    proof: GuardianProof,
+) -> Result<()> {
+    require!(amount > 0, WalletError::InvalidOperation);
+    let now = ctx.accounts.clock.unix_timestamp;
+    require!(timestamp <= now + 60, WalletError::FutureTimestamp);
+    require!(timestamp >= now - 300, WalletError::ExpiredTimestamp);
+    
+    let wallet_key = ctx.accounts.wallet.key();
+    let mut wallet = ctx.accounts.wallet.load_mut()?;
+    // Đề xuất đầu tiên kết thúc giai đoạn thiết lập: ví phải hợp lệ từ đây
+    wallet.check_invariants()?;
+    
+    let mut content = Vec::new();
+    amount.serialize(&mut content)?;
+    destination.serialize(&mut content)?;
+    
+    let context = SigningContext {
+        instruction_sysvar: &ctx.accounts.instruction_sysvar,
+        multisig: wallet_key,
+        rp_id_hash: wallet.rp_id_hash,
+        signers: signer_keys(ctx.accounts.payer.as_ref(), ctx.remaining_accounts),
+    };
+    let proposal_count = wallet.proposal_count;
+    let index = wallet.slot_index(proposer_guardian_id)?;
+    let slot = &mut wallet.guardians[index];
+    require!(slot.is_active(), WalletError::InactiveGuardian);
+    let signer = SignerState {
+        guardian_id: proposer_guardian_id,
+        auth: slot.auth(),
+        nonce: &mut slot.nonce,
+        sign_count: &mut slot.sign_count,
+    };
+    authenticate_signer(
+        &context,
+        signer,
+        &proof,
+        timestamp,
+        SigningAction::CreateProposal {
+            proposal_id: proposal_count,
+            content_hash: hash(&content).to_bytes(),
+        },
+    )?;
+    require!(slot.has_permission(PERMISSION_PROPOSE), WalletError::PermissionDenied);
+    
+    let proposal_id = wallet.proposal_count;
+    wallet.proposal_count = proposal_id.checked_add(1).ok_or(WalletError::ArithmeticOverflow)?;
+    
+    let mut proposal = ctx.accounts.proposal.load_init()?;
+    proposal.version = ACCOUNT_VERSION;
+    proposal.wallet = ctx.accounts.wallet.key();
+    proposal.destination = destination;
+    proposal.proposer = ctx.accounts.payer.key();
+    proposal.proposal_id = proposal_id;
+    proposal.amount = amount;
+    proposal.created_at = ctx.accounts.clock.unix_timestamp;
+    proposal.status = LARGE_PROPOSAL_PENDING;
+    proposal.required_signatures = wallet.threshold;
+    proposal.bump = ctx.bumps.proposal;
+    
+    msg!("Đã tạo đề xuất {} chuyển {} lamports đến {}", proposal_id, amount, destination);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ApproveLargeProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"large_multisig".as_ref(), &wallet.load()?.address_seed],
+        bump = wallet.load()?.bump
+    )]
+    pub wallet: AccountLoader<'info, LargeMultiSigWallet>,
+    
+    #[account(
+        mut,
+        seeds = [b"large_proposal".as_ref(), wallet.key().as_ref(), &proposal_id.to_le_bytes()],
+        bump = proposal.load()?.bump,
+        constraint = proposal.load()?.status == LARGE_PROPOSAL_PENDING @ WalletError::InvalidOperation
+    )]
+    pub proposal: AccountLoader<'info, LargeProposal>,
+    
+    pub payer: Signer<'info>,
+    
+    /// CHECK: Đây là tài khoản sysvar chứa thông tin về các instruction trong transaction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+    
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Phê duyệt đề xuất bằng guardian trong bảng của ví. Chỉ chấp nhận payload v1,
+/// cùng `SigningAction::ApproveProposal` như ví thường.
+pub fn approve_large_proposal(
+    ctx: Context<ApproveLargeProposal>,
+    proposal_id: u64,
+    guardian_id: u64,
+    timestamp: i64,
+    proof: GuardianProof,
+) -> Result<()> {
+    let now = ctx.accounts.clock.unix_timestamp;
+    require!(timestamp <= now + 60, WalletError::FutureTimestamp);
+    require!(timestamp >= now - 300, WalletError::ExpiredTimestamp);
+    
+    let wallet_key = ctx.accounts.wallet.key();
+    let proposal_key = ctx.accounts.proposal.key();
+    let mut wallet = ctx.accounts.wallet.load_mut()?;
+    let mut proposal = ctx.accounts.proposal.load_mut()?;
+    
+    let index = wallet.slot_index(guardian_id)?;
+    let bit = 1u64 << index;
+    require!(proposal.approvals & bit == 0, WalletError::AlreadyVoted);
+    
+    let context = SigningContext {
+        instruction_sysvar: &ctx.accounts.instruction_sysvar,
+        multisig: wallet_key,
+        rp_id_hash: wallet.rp_id_hash,
+        signers: signer_keys(ctx.accounts.payer.as_ref(), ctx.remaining_accounts),
+    };
+    let slot = &mut wallet.guardians[index];
+    require!(slot.is_active(), WalletError::InactiveGuardian);
+    require!(slot.has_permission(PERMISSION_APPROVE), WalletError::PermissionDenied);
+    let signer = SignerState {
+        guardian_id,
+        auth: slot.auth(),
+        nonce: &mut slot.nonce,
+        sign_count: &mut slot.sign_count,
+    };
+    authenticate_signer(
+        &context,
+        signer,
+        &proof,
+        timestamp,
+        SigningAction::ApproveProposal {
+            proposal: proposal_key,
+            proposal_id,
+            guardian_id,
+        },
+    )?;
+    
+    proposal.approvals |= bit;
+    proposal.signatures_count += 1;
+    
+    msg!("Guardian {} đã phê duyệt đề xuất {}", guardian_id, proposal_id);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ExecuteLargeProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"large_multisig".as_ref(), &wallet.load()?.address_seed],
+        bump = wallet.load()?.bump
+    )]
+    pub wallet: AccountLoader<'info, LargeMultiSigWallet>,
+    
+    #[account(
+        mut,
+        seeds = [b"large_proposal".as_ref(), wallet.key().as_ref(), &proposal_id.to_le_bytes()],
+        bump = proposal.load()?.bump,
+        constraint = proposal.load()?.status == LARGE_PROPOSAL_PENDING @ WalletError::InvalidOperation,
+        constraint = proposal.load()?.signatures_count >= proposal.load()?.required_signatures @ WalletError::InvalidOperation
+    )]
+    pub proposal: AccountLoader<'info, LargeProposal>,
+    
+    /// CHECK: Địa chỉ nhận SOL, phải khớp với đề xuất
+    #[account(mut, address = proposal.load()?.destination @ WalletError::InvalidOperation)]
+    pub destination: AccountInfo<'info>,
+    
+    pub payer: Signer<'info>,
+    
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn execute_large_proposal(ctx: Context<ExecuteLargeProposal>, proposal_id: u64) -> Result<()> {
+    let mut proposal = ctx.accounts.proposal.load_mut()?;
+    let amount = proposal.amount;
+    let wallet_info = ctx.accounts.wallet.to_account_info();
+    let destination_info = &ctx.accounts.destination;
+    
+    let dest_starting_lamports = destination_info.lamports();
+    **destination_info.lamports.borrow_mut() = dest_starting_lamports
+        .checked_add(amount)
+        .ok_or(WalletError::ArithmeticOverflow)?;
+    
+    let wallet_starting_lamports = wallet_info.lamports();
+    **wallet_info.lamports.borrow_mut() = wallet_starting_lamports
+        .checked_sub(amount)
+        .ok_or(WalletError::InsufficientFunds)?;
+    
+    proposal.status = LARGE_PROPOSAL_EXECUTED;
+    proposal.executed_at = ctx.accounts.clock.unix_timestamp;
+    
+    let mut wallet = ctx.accounts.wallet.load_mut()?;
+    wallet.transaction_nonce = wallet.transaction_nonce.checked_add(1).ok_or(WalletError::ArithmeticOverflow)?;
+    
+    msg!("Đã thực thi đề xuất {}: chuyển {} lamports đến {}", proposal_id, amount, destination_info.key());
+    
+    Ok(())
+}
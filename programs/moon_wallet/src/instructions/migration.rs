@@ -0,0 +1,527 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use crate::state::*;
+use crate::state::legacy::*;
+use crate::errors::*;
+use anchor_lang::solana_program::hash::hash;
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: Tài khoản layout cũ của chương trình, loại tài khoản được xác định qua discriminator
+    #[account(mut, owner = crate::ID @ WalletError::InvalidOperation)]
+    pub target: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Thông tin ví không có trong layout cũ, do owner cung cấp khi chuyển tài khoản ví
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct WalletMigrationParams {
+    pub rp_id: String,               // WebAuthn relying party ID của ví
+}
+
+/// Nâng cấp một tài khoản layout cũ lên `ACCOUNT_VERSION`: nạp thêm rent từ payer, realloc và ghi lại.
+///
+/// Guardian, đề xuất và chữ ký có thể được chuyển bởi bất kỳ ai. Ví chỉ do owner chuyển với
+/// `wallet_params`, sau khi mọi guardian của ví đã được chuyển và truyền qua remaining_accounts.
+/// Đề xuất được chuyển sau ví, với ví (writable) trong remaining_accounts để ví ghi nhận ID và
+/// trạng thái của đề xuất.
+///
+/// ID đề xuất ở layout cũ do client chọn, nên `proposal_count` không được khai báo mà tăng dần khi
+/// từng đề xuất được chuyển. Trước khi mọi đề xuất cũ được chuyển, create_proposal có thể trùng PDA
+/// với một đề xuất cũ và thất bại, không ghi đè được đề xuất đó.
+pub fn migrate_account<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MigrateAccount<'info>>,
+    wallet_params: Option<WalletMigrationParams>,
+) -> Result<()> {
+    let target = ctx.accounts.target.to_account_info();
+    let data = target.try_borrow_data()?;
+    require!(data.len() > 8, WalletError::InvalidOperation);
+    let discriminator = &data[..8];
+    
+    if discriminator == MultiSigWallet::DISCRIMINATOR {
+        require!(!is_current::<MultiSigWallet>(&data), WalletError::AccountAlreadyMigrated);
+        let legacy = MultiSigWalletV1::deserialize(&mut &data[8..])?;
+        drop(data);
+        require!(ctx.accounts.payer.key() == legacy.owner, WalletError::InvalidOwner);
+        let params = wallet_params.ok_or(WalletError::InvalidOperation)?;
+        let wallet = migrate_wallet(target.key(), legacy, params, ctx.remaining_accounts)?;
+        write_account(&target, &ctx.accounts.payer, &ctx.accounts.system_program, 8 + MultiSigWallet::INIT_SPACE, &wallet)?;
+    } else if discriminator == Guardian::DISCRIMINATOR {
+        require!(!is_current::<Guardian>(&data), WalletError::AccountAlreadyMigrated);
+        let legacy = GuardianV1::deserialize(&mut &data[8..])?;
+        drop(data);
+        let guardian = migrate_guardian(legacy);
+        write_account(&target, &ctx.accounts.payer, &ctx.accounts.system_program, 8 + Guardian::INIT_SPACE, &guardian)?;
+    } else if discriminator == TransactionProposal::DISCRIMINATOR {
+        require!(!is_current::<TransactionProposal>(&data), WalletError::AccountAlreadyMigrated);
+        let legacy = TransactionProposalV1::deserialize(&mut &data[8..])?;
+        drop(data);
+        let proposal = migrate_proposal(legacy);
+        record_migrated_proposal(&proposal, ctx.remaining_accounts)?;
+        let space = proposal.space();
+        write_account(&target, &ctx.accounts.payer, &ctx.accounts.system_program, space, &proposal)?;
+    } else if discriminator == ProposalSignature::DISCRIMINATOR {
+        require!(!is_current::<ProposalSignature>(&data), WalletError::AccountAlreadyMigrated);
+        let legacy = ProposalSignatureV1::deserialize(&mut &data[8..])?;
+        drop(data);
+        let signature = ProposalSignature {
+            version: ACCOUNT_VERSION,
+            proposal: legacy.proposal,
+            guardian_id: legacy.guardian_id,
+            signature_time: legacy.signature_time,
+            bump: legacy.bump,
+            // Layout cũ chỉ ghi nhận phê duyệt
+            approved: true,
+            weight: 1,
+            reserved: [0; SIGNATURE_RESERVED_LEN],
+        };
+        write_account(&target, &ctx.accounts.payer, &ctx.accounts.system_program, 8 + ProposalSignature::INIT_SPACE, &signature)?;
+    } else {
+        // RecoveryRequest, RecoveryApproval và OwnerDevice chưa từng có layout cũ
+        return err!(WalletError::AccountAlreadyMigrated);
+    }
+    
+    msg!("Đã chuyển tài khoản {} lên phiên bản {}", target.key(), ACCOUNT_VERSION);
+    
+    Ok(())
+}
+
+/// Tài khoản đã ở layout hiện tại nếu byte version khớp và dữ liệu đọc được theo layout mới
+fn is_current<T: AccountDeserialize>(data: &[u8]) -> bool {
+    data[8] == ACCOUNT_VERSION && T::try_deserialize(&mut &data[..]).is_ok()
+}
+
+fn migrate_guardian(legacy: GuardianV1) -> Guardian {
+    // Owner giữ toàn quyền như trước, guardian khác chỉ đề xuất và phê duyệt
+    let permissions = if legacy.is_owner {
+        PERMISSION_ALL
+    } else {
+        PERMISSION_PROPOSE | PERMISSION_APPROVE
+    };
+    
+    Guardian {
+        version: ACCOUNT_VERSION,
+        wallet: legacy.wallet,
+        guardian_id: legacy.guardian_id,
+        name: legacy.name,
+        is_active: legacy.is_active,
+        recovery_hash: legacy.recovery_hash,
+        is_owner: legacy.is_owner,
+        auth: legacy.webauthn_pubkey.map(GuardianAuth::Passkey),
+        bump: legacy.bump,
+        sign_count: 0,
+        nonce: 0,
+        device_count: 0,
+        device_epoch: 0,
+        permissions,
+        spending_limits: Vec::new(),
+        weight: 1,
+        reserved: [0; GUARDIAN_RESERVED_LEN],
+    }
+}
+
+fn migrate_wallet(
+    wallet_key: Pubkey,
+    legacy: MultiSigWalletV1,
+    params: WalletMigrationParams,
+    guardian_accounts: &[AccountInfo],
+) -> Result<MultiSigWallet> {
+    require!(!params.rp_id.is_empty(), WalletError::InvalidConfig);
+    
+    let mut wallet = MultiSigWallet {
+        version: ACCOUNT_VERSION,
+        threshold: legacy.threshold,
+        guardian_count: legacy.guardian_count,
+        active_guardian_count: 0,
+        owner_count: 0,
+        approver_count: 0,
+        recovery_nonce: legacy.recovery_nonce,
+        bump: legacy.bump,
+        transaction_nonce: legacy.transaction_nonce,
+        last_transaction_timestamp: legacy.last_transaction_timestamp,
+        owner: legacy.owner,
+        credential_id: legacy.credential_id,
+        timelock: TimelockConfig::default(),
+        spending_policy: SpendingPolicy::restricted(),
+        rp_id_hash: hash(params.rp_id.as_bytes()).to_bytes(),
+        seed_version: SEED_VERSION_LEGACY,
+        salt: [0; 32],
+        migrated_from: None,
+        pending_proposals: 0,
+        guardians: Vec::new(),
+        // Tăng dần khi các đề xuất cũ được chuyển, xem `record_proposal`
+        proposal_count: 0,
+        weighted_voting: false,
+        approver_weight: 0,
+        reserved: [0; WALLET_RESERVED_LEN],
+    };
+    
+    // Bộ đếm guardian được tính lại từ các guardian đã chuyển
+    for info in guardian_accounts {
+        require!(*info.owner == crate::ID, WalletError::InvalidGuardian);
+        let guardian = Guardian::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require!(guardian.wallet == wallet_key, WalletError::InvalidGuardian);
+        let (guardian_pda, _) = Pubkey::find_program_address(
+            &[b"guardian".as_ref(), wallet_key.as_ref(), &guardian.guardian_id.to_le_bytes()],
+            &crate::ID,
+        );
+        require!(info.key() == guardian_pda, WalletError::InvalidGuardian);
+        require!(
+            wallet.guardians.iter().all(|entry| entry.guardian_id != guardian.guardian_id),
+            WalletError::InvalidGuardian
+        );
+        
+        if guardian.is_active {
+            wallet.active_guardian_count += 1;
+            if guardian.is_owner {
+                wallet.owner_count += 1;
+            }
+        }
+        wallet.update_approvers(0, guardian.approver_weight());
+        wallet.sync_guardian_entry(&guardian)?;
+    }
+    require!(wallet.guardians.len() == wallet.guardian_count as usize, WalletError::InvalidGuardian);
+    
+    Ok(wallet)
+}
+
+/// Ghi đề xuất vừa chuyển vào ví của nó. Ví phải đã ở layout hiện tại.
+fn record_migrated_proposal<'info>(
+    proposal: &TransactionProposal,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    let wallet_info = remaining_accounts
+        .iter()
+        .find(|account| *account.key == proposal.multisig)
+        .ok_or(WalletError::WalletNotMigrated)?;
+    require!(
+        *wallet_info.owner == crate::ID && is_current::<MultiSigWallet>(&wallet_info.try_borrow_data()?),
+        WalletError::WalletNotMigrated
+    );
+    require!(wallet_info.is_writable, WalletError::InvalidOperation);
+    
+    let mut wallet = Account::<MultiSigWallet>::try_from(wallet_info)?;
+    record_proposal(&mut wallet, proposal)?;
+    wallet.exit(&crate::ID)
+}
+
+/// `proposal_count` luôn vượt mọi ID đã dùng để create_proposal không cấp lại ID của đề xuất cũ
+fn record_proposal(wallet: &mut MultiSigWallet, proposal: &TransactionProposal) -> Result<()> {
+    let next_id = proposal.proposal_id.checked_add(1).ok_or(WalletError::ArithmeticOverflow)?;
+    wallet.proposal_count = wallet.proposal_count.max(next_id);
+    if proposal.status == ProposalStatus::Pending {
+        wallet.pending_proposals = wallet.pending_proposals.checked_add(1).ok_or(WalletError::ArithmeticOverflow)?;
+    }
+    Ok(())
+}
+
+fn migrate_proposal(legacy: TransactionProposalV1) -> TransactionProposal {
+    let status = match legacy.status {
+        ProposalStatusV1::Pending => ProposalStatus::Pending,
+        ProposalStatusV1::Executed => ProposalStatus::Executed,
+        ProposalStatusV1::Rejected => ProposalStatus::Rejected,
+        ProposalStatusV1::Expired => ProposalStatus::Expired,
+    };
+    
+    TransactionProposal {
+        version: ACCOUNT_VERSION,
+        multisig: legacy.multisig,
+        proposal_id: legacy.proposal_id,
+        proposer: legacy.proposer,
+        description: legacy.description,
+        action: legacy.action,
+        params: legacy.params,
+        status,
+        created_at: legacy.created_at,
+        executed_at: legacy.executed_at,
+        signatures_count: legacy.signatures_count,
+        rejections_count: 0,
+        required_signatures: legacy.required_signatures,
+        // Layout cũ không lưu số người phê duyệt: lấy cận trên để phiếu từ chối không tự đóng đề xuất cũ
+        approver_count: MAX_GUARDIANS,
+        bump: legacy.bump,
+        instructions: Vec::new(),
+        actions: Vec::new(),
+        expires_at: None,
+        config_action: None,
+        weighted: false,
+        approval_weight: 0,
+        rejection_weight: 0,
+        total_weight: 0,
+        reserved: [0; PROPOSAL_RESERVED_LEN],
+    }
+}
+
+/// Nạp thêm rent cho kích thước mới, realloc rồi ghi tài khoản theo layout hiện tại
+fn write_account<'info, T: AccountSerialize>(
+    target: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    space: usize,
+    account: &T,
+) -> Result<()> {
+    let space = space.max(target.data_len());
+    let top_up = Rent::get()?.minimum_balance(space).saturating_sub(target.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: payer.to_account_info(),
+                    to: target.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    target.realloc(space, true)?;
+    
+    let mut data = target.try_borrow_mut_data()?;
+    account.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const RP_ID: &str = "moonwallet.app";
+    
+    // Dữ liệu layout cũ được serialize rồi đọc lại như trong `migrate_account`
+    fn roundtrip<T: AnchorSerialize, U: AnchorDeserialize>(legacy: &T) -> U {
+        let bytes = legacy.try_to_vec().unwrap();
+        U::deserialize(&mut &bytes[..]).unwrap()
+    }
+    
+    fn legacy_guardian(wallet: Pubkey, guardian_id: u64, is_owner: bool) -> GuardianV1 {
+        GuardianV1 {
+            wallet,
+            guardian_id,
+            name: format!("guardian {}", guardian_id),
+            is_active: true,
+            recovery_hash: [7; 32],
+            is_owner,
+            webauthn_pubkey: Some([2; 33]),
+            bump: 254,
+        }
+    }
+    
+    fn legacy_wallet(owner: Pubkey, guardian_count: u8) -> MultiSigWalletV1 {
+        MultiSigWalletV1 {
+            threshold: 1,
+            guardian_count,
+            recovery_nonce: 3,
+            bump: 253,
+            transaction_nonce: 9,
+            last_transaction_timestamp: 1_700_000_000,
+            owner,
+            credential_id: "credential".to_string(),
+        }
+    }
+    
+    fn params() -> WalletMigrationParams {
+        WalletMigrationParams {
+            rp_id: RP_ID.to_string(),
+        }
+    }
+    
+    fn guardian_pda(wallet: &Pubkey, guardian_id: u64) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"guardian".as_ref(), wallet.as_ref(), &guardian_id.to_le_bytes()],
+            &crate::ID,
+        ).0
+    }
+    
+    fn account_data<T: AccountSerialize>(account: &T) -> Vec<u8> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data
+    }
+    
+    /// Chủ sở hữu, lamports và dữ liệu của một tài khoản trong remaining_accounts
+    struct TestAccount {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+    }
+    
+    impl TestAccount {
+        fn program_owned(key: Pubkey, data: Vec<u8>) -> Self {
+            Self { key, owner: crate::ID, lamports: 1_000_000, data }
+        }
+        
+        fn info(&mut self) -> AccountInfo<'_> {
+            AccountInfo::new(&self.key, false, true, &mut self.lamports, &mut self.data, &self.owner, false, 0)
+        }
+    }
+    
+    fn run_migrate_wallet(
+        wallet_key: Pubkey,
+        legacy: &MultiSigWalletV1,
+        params: WalletMigrationParams,
+        accounts: &mut [TestAccount],
+    ) -> Result<MultiSigWallet> {
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(TestAccount::info).collect();
+        migrate_wallet(wallet_key, roundtrip(legacy), params, &infos)
+    }
+    
+    #[test]
+    fn migrate_guardian_keeps_fields_and_assigns_permissions() {
+        let wallet = Pubkey::new_unique();
+        
+        let owner = migrate_guardian(roundtrip(&legacy_guardian(wallet, 1, true)));
+        assert_eq!(owner.version, ACCOUNT_VERSION);
+        assert_eq!(owner.wallet, wallet);
+        assert_eq!(owner.guardian_id, 1);
+        assert_eq!(owner.name, "guardian 1");
+        assert!(owner.is_active && owner.is_owner);
+        assert_eq!(owner.recovery_hash, [7; 32]);
+        assert!(matches!(owner.auth, Some(GuardianAuth::Passkey(pubkey)) if pubkey == [2; 33]));
+        assert_eq!(owner.bump, 254);
+        assert_eq!((owner.sign_count, owner.nonce, owner.device_count), (0, 0, 0));
+        assert_eq!(owner.permissions, PERMISSION_ALL);
+        assert_eq!(owner.weight, 1);
+        
+        let member = migrate_guardian(roundtrip(&GuardianV1 {
+            webauthn_pubkey: None,
+            ..legacy_guardian(wallet, 2, false)
+        }));
+        assert_eq!(member.permissions, PERMISSION_PROPOSE | PERMISSION_APPROVE);
+        assert!(member.auth.is_none());
+        assert!(!member.is_approver());
+    }
+    
+    fn legacy_proposal(multisig: Pubkey, proposal_id: u64, status: ProposalStatusV1) -> TransactionProposalV1 {
+        TransactionProposalV1 {
+            multisig,
+            proposal_id,
+            proposer: Pubkey::new_unique(),
+            description: "tiền thuê nhà".to_string(),
+            action: "transfer".to_string(),
+            params: ActionParams {
+                amount: Some(1_000),
+                destination: Some(Pubkey::new_unique()),
+                token_mint: None,
+            },
+            status,
+            created_at: 100,
+            executed_at: Some(200),
+            signatures_count: 2,
+            required_signatures: 2,
+            bump: 252,
+        }
+    }
+    
+    #[test]
+    fn migrate_proposal_maps_status_and_resets_new_fields() {
+        let legacy = legacy_proposal(Pubkey::new_unique(), 5, ProposalStatusV1::Executed);
+        
+        let proposal = migrate_proposal(roundtrip(&legacy));
+        assert_eq!(proposal.version, ACCOUNT_VERSION);
+        assert_eq!(proposal.multisig, legacy.multisig);
+        assert_eq!(proposal.proposal_id, 5);
+        assert_eq!(proposal.proposer, legacy.proposer);
+        assert_eq!(proposal.description, legacy.description);
+        assert_eq!(proposal.action, "transfer");
+        assert_eq!(proposal.params.amount, Some(1_000));
+        assert_eq!(proposal.params.destination, legacy.params.destination);
+        assert!(proposal.status == ProposalStatus::Executed);
+        assert_eq!((proposal.created_at, proposal.executed_at), (100, Some(200)));
+        assert_eq!((proposal.signatures_count, proposal.rejections_count), (2, 0));
+        assert_eq!(proposal.required_signatures, 2);
+        assert_eq!(proposal.bump, 252);
+        assert!(proposal.instructions.is_empty() && proposal.actions.is_empty());
+        assert!(proposal.expires_at.is_none() && proposal.config_action.is_none());
+        assert!(!proposal.weighted);
+        
+        let pending = migrate_proposal(roundtrip(&TransactionProposalV1 {
+            status: ProposalStatusV1::Pending,
+            ..legacy
+        }));
+        assert!(pending.status == ProposalStatus::Pending);
+    }
+    
+    #[test]
+    fn migrate_wallet_recounts_guardians() {
+        let wallet_key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let guardian = migrate_guardian(legacy_guardian(wallet_key, 1, true));
+        let revoked = migrate_guardian(GuardianV1 {
+            webauthn_pubkey: None,
+            ..legacy_guardian(wallet_key, 2, false)
+        });
+        let mut accounts = [
+            TestAccount::program_owned(guardian_pda(&wallet_key, 1), account_data(&guardian)),
+            TestAccount::program_owned(guardian_pda(&wallet_key, 2), account_data(&revoked)),
+        ];
+        
+        let wallet = run_migrate_wallet(wallet_key, &legacy_wallet(owner, 2), params(), &mut accounts).unwrap();
+        assert_eq!(wallet.version, ACCOUNT_VERSION);
+        assert_eq!(wallet.owner, owner);
+        assert_eq!(wallet.credential_id, "credential");
+        assert_eq!((wallet.threshold, wallet.guardian_count), (1, 2));
+        assert_eq!((wallet.recovery_nonce, wallet.transaction_nonce, wallet.bump), (3, 9, 253));
+        assert_eq!(wallet.last_transaction_timestamp, 1_700_000_000);
+        assert_eq!((wallet.active_guardian_count, wallet.owner_count), (2, 1));
+        assert_eq!((wallet.approver_count, wallet.approver_weight), (1, 1));
+        assert_eq!(wallet.guardians.len(), 2);
+        assert_eq!((wallet.proposal_count, wallet.pending_proposals), (0, 0));
+        assert_eq!(wallet.rp_id_hash, hash(RP_ID.as_bytes()).to_bytes());
+        assert_eq!(wallet.seed_version, SEED_VERSION_LEGACY);
+        assert!(wallet.spending_policy.enabled && wallet.spending_policy.limits.is_empty());
+        assert!(wallet.check_invariants().is_ok());
+    }
+    
+    #[test]
+    fn migrate_wallet_requires_every_guardian() {
+        let wallet_key = Pubkey::new_unique();
+        let guardian = migrate_guardian(legacy_guardian(wallet_key, 1, true));
+        let mut accounts = [
+            TestAccount::program_owned(guardian_pda(&wallet_key, 1), account_data(&guardian)),
+        ];
+        
+        assert_eq!(
+            run_migrate_wallet(wallet_key, &legacy_wallet(Pubkey::new_unique(), 2), params(), &mut accounts).err().unwrap(),
+            WalletError::InvalidGuardian.into()
+        );
+    }
+    
+    #[test]
+    fn migrated_proposals_advance_proposal_count() {
+        let wallet_key = Pubkey::new_unique();
+        let mut wallet = test_wallet(1, 1, 1, 1);
+        
+        for (proposal_id, status) in [
+            (7, ProposalStatusV1::Executed),
+            (2, ProposalStatusV1::Pending),
+            (5, ProposalStatusV1::Pending),
+            (3, ProposalStatusV1::Rejected),
+        ] {
+            let proposal = migrate_proposal(legacy_proposal(wallet_key, proposal_id, status));
+            record_proposal(&mut wallet, &proposal).unwrap();
+        }
+        assert_eq!((wallet.proposal_count, wallet.pending_proposals), (8, 2));
+        
+        let last = migrate_proposal(legacy_proposal(wallet_key, u64::MAX, ProposalStatusV1::Executed));
+        assert_eq!(record_proposal(&mut wallet, &last).unwrap_err(), WalletError::ArithmeticOverflow.into());
+    }
+    
+    #[test]
+    fn proposal_migration_requires_migrated_wallet() {
+        let wallet_key = Pubkey::new_unique();
+        let proposal = migrate_proposal(legacy_proposal(wallet_key, 0, ProposalStatusV1::Pending));
+        let legacy = legacy_wallet(Pubkey::new_unique(), 1);
+        let mut legacy_data = MultiSigWallet::DISCRIMINATOR.to_vec();
+        legacy_data.extend(legacy.try_to_vec().unwrap());
+        
+        let mut accounts = [TestAccount::program_owned(wallet_key, legacy_data)];
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(TestAccount::info).collect();
+        assert_eq!(record_migrated_proposal(&proposal, &infos).unwrap_err(), WalletError::WalletNotMigrated.into());
+        assert_eq!(record_migrated_proposal(&proposal, &[]).unwrap_err(), WalletError::WalletNotMigrated.into());
+    }
+}
@@ -1,7 +1,17 @@
 pub mod wallet;
 pub mod guardian;
 pub mod proposal;
+pub mod recovery;
+pub mod device;
+pub mod migration;
+#[cfg(feature = "experimental-large-wallet")]
+pub mod large_wallet;
 
 pub use wallet::*;
 pub use guardian::*;
-pub use proposal::*; 
\ No newline at end of file
+pub use proposal::*;
+pub use recovery::*;
+pub use device::*;
+pub use migration::*;
+#[cfg(feature = "experimental-large-wallet")]
+pub use large_wallet::*;
@@ -1,17 +1,22 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::*;
-use crate::instructions::wallet::process_credential_id_seed;
-use anchor_lang::solana_program::sysvar::instructions::load_instruction_at_checked;
+use crate::instructions::wallet::{migrate_wallet_seeds, multisig_address_seed, transfer_tokens_from_vault};
+use crate::instructions::guardian::{create_guardian_account, create_program_account, load_guardian};
+use crate::auth::{authenticate_guardian, signer_keys, GuardianProof, SigningContext};
+use crate::payload::SigningAction;
 use anchor_lang::solana_program::hash::hash;
-use std::str::FromStr;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_interface::{self, CloseAccount, Mint, TokenAccount, TokenInterface};
 
 #[derive(Accounts)]
-#[instruction(proposal_id: u64, description: String, proposer_guardian_id: u64)]
+#[instruction(description: String, proposer_guardian_id: u64, action: String, params: ActionParams, instructions: Vec<ProposalInstruction>, actions: Vec<ProposalAction>, expires_at: Option<i64>, config_action: Option<ConfigAction>)]
 pub struct CreateProposal<'info> {
     #[account(
         mut,
-        seeds = [b"multisig".as_ref(), &process_credential_id_seed(&multisig.credential_id)],
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
         bump = multisig.bump
     )]
     pub multisig: Account<'info, MultiSigWallet>,
@@ -19,29 +24,18 @@ pub struct CreateProposal<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + 
-                32 +    
-                8 +  
-                32 +  
-                4 + description.len() + 
-                4 + 10 +  
-                1 + 8 + 32 + 32 + 
-                1 +  
-                8 +  
-                1 + 8 + 
-                1 +  
-                1 +  
-                1,    
+        space = TransactionProposal::space_for(&description, &action, &instructions, &actions, config_action.as_ref()),
         seeds = [
             b"proposal".as_ref(), 
             multisig.key().as_ref(), 
-            &proposal_id.to_le_bytes()
+            &multisig.proposal_count.to_le_bytes()
         ],
         bump
     )]
     pub proposal: Account<'info, TransactionProposal>,
     
     #[account(
+        mut,
         seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &proposer_guardian_id.to_le_bytes()],
         bump = proposer_guardian.bump
     )]
@@ -50,32 +44,126 @@ pub struct CreateProposal<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     
+    /// CHECK: Đây là tài khoản sysvar chứa thông tin về các instruction trong transaction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+    
     pub clock: Sysvar<'info, Clock>,
     pub system_program: Program<'info, System>,
 }
 
+#[allow(clippy::too_many_arguments)]
+/// ID đề xuất được cấp tuần tự từ `multisig.proposal_count`. Guardian đề xuất phải ký
+/// `SigningAction::CreateProposal` gắn với ID này và nội dung đề xuất.
 pub fn create_proposal(
     ctx: Context<CreateProposal>, 
-    proposal_id: u64,
     description: String,
     _proposer_guardian_id: u64,
     action: String,
-    params: ActionParams
+    params: ActionParams,
+    instructions: Vec<ProposalInstruction>,
+    actions: Vec<ProposalAction>,
+    expires_at: Option<i64>,
+    config_action: Option<ConfigAction>,
+    timestamp: i64,
+    proof: GuardianProof
 ) -> Result<()> {
-    let multisig = &ctx.accounts.multisig;
+    let multisig = &mut ctx.accounts.multisig;
     let proposal = &mut ctx.accounts.proposal;
-    let guardian = &ctx.accounts.proposer_guardian;
+    let guardian = &mut ctx.accounts.proposer_guardian;
     let clock = &ctx.accounts.clock;
     
     require!(guardian.is_active, WalletError::InactiveGuardian);
     
+    require!(
+        timestamp <= clock.unix_timestamp + 60, 
+        WalletError::FutureTimestamp
+    );
+    
+    require!(
+        timestamp >= clock.unix_timestamp - 300,
+        WalletError::ExpiredTimestamp
+    );
+    
+    let mut content = Vec::new();
+    description.serialize(&mut content)?;
+    action.serialize(&mut content)?;
+    params.serialize(&mut content)?;
+    instructions.serialize(&mut content)?;
+    actions.serialize(&mut content)?;
+    expires_at.serialize(&mut content)?;
+    config_action.serialize(&mut content)?;
+    
+    let context = SigningContext {
+        instruction_sysvar: &ctx.accounts.instruction_sysvar,
+        multisig: multisig.key(),
+        rp_id_hash: multisig.rp_id_hash,
+        signers: signer_keys(ctx.accounts.payer.as_ref(), ctx.remaining_accounts),
+    };
+    authenticate_guardian(
+        &context,
+        guardian,
+        &proof,
+        timestamp,
+        SigningAction::CreateProposal {
+            proposal_id: multisig.proposal_count,
+            content_hash: hash(&content).to_bytes(),
+        },
+    )?;
+    
+    require!(guardian.has_permission(PERMISSION_PROPOSE), WalletError::PermissionDenied);
+    
     require!(description.len() <= 100, WalletError::NameTooLong);
     
+    if let Some(expires_at) = expires_at {
+        require!(expires_at > clock.unix_timestamp, WalletError::InvalidExpiry);
+    }
+    
     require!(
-        action == "transfer", 
+        action == "transfer" || action == "token_transfer" || action == "invoke" || action == "batch" || action == "config", 
         WalletError::UnsupportedAction
     );
     
+    if action == "token_transfer" {
+        require!(params.token_mint.is_some(), WalletError::InvalidOperation);
+    }
+    
+    if action == "invoke" {
+        require!(!instructions.is_empty(), WalletError::InvalidOperation);
+        for ix in instructions.iter() {
+            require!(ix.program_id != crate::ID, WalletError::InvalidCpiProgram);
+        }
+    } else {
+        require!(instructions.is_empty(), WalletError::InvalidOperation);
+    }
+    
+    if action == "batch" {
+        require!(!actions.is_empty(), WalletError::InvalidOperation);
+        require!(actions.len() <= MAX_BATCH_ACTIONS, WalletError::LimitExceeded);
+        for (i, step) in actions.iter().enumerate() {
+            validate_batch_step(step).inspect_err(|_| {
+                msg!("Bước {} ({}) không hợp lệ", i, step.action);
+            })?;
+        }
+    } else {
+        require!(actions.is_empty(), WalletError::InvalidOperation);
+    }
+    
+    match (action.as_str(), &config_action) {
+        ("config", Some(config)) => {
+            require!(guardian.has_permission(PERMISSION_ADMIN), WalletError::PermissionDenied);
+            config.validate()?
+        },
+        ("config", None) => return Err(WalletError::InvalidOperation.into()),
+        (_, Some(_)) => return Err(WalletError::InvalidOperation.into()),
+        (_, None) => {}
+    }
+    
+    let proposal_id = multisig.proposal_count;
+    multisig.proposal_count = proposal_id.checked_add(1).ok_or(WalletError::ArithmeticOverflow)?;
+    multisig.pending_proposals = multisig.pending_proposals.checked_add(1).ok_or(WalletError::ArithmeticOverflow)?;
+    
+    proposal.version = ACCOUNT_VERSION;
     proposal.multisig = multisig.key();
     proposal.proposal_id = proposal_id;
     proposal.proposer = ctx.accounts.payer.key();
@@ -86,18 +174,58 @@ pub fn create_proposal(
     proposal.created_at = clock.unix_timestamp;
     proposal.executed_at = None;
     proposal.signatures_count = 0; 
+    proposal.rejections_count = 0;
     proposal.required_signatures = multisig.threshold;
+    proposal.approver_count = multisig.approver_count;
+    proposal.weighted = multisig.weighted_voting;
+    proposal.approval_weight = 0;
+    proposal.rejection_weight = 0;
+    proposal.total_weight = multisig.approver_weight;
     proposal.bump = ctx.bumps.proposal;
+    proposal.instructions = instructions;
+    proposal.actions = actions;
+    proposal.expires_at = expires_at;
+    proposal.config_action = config_action;
     
     msg!("Đã tạo đề xuất giao dịch thành công với ID: {}", proposal_id);
     
     Ok(())
 }
 
+fn validate_batch_step(step: &ProposalAction) -> Result<()> {
+    let params = &step.params;
+    match step.action.as_str() {
+        "transfer" => {
+            require!(
+                params.amount.is_some() && params.destination.is_some(),
+                WalletError::InvalidBatchStep
+            );
+        },
+        "token_transfer" => {
+            require!(
+                params.amount.is_some() && params.destination.is_some() && params.token_mint.is_some(),
+                WalletError::InvalidBatchStep
+            );
+        },
+        "close_token_account" => {
+            require!(
+                params.destination.is_some() && params.token_mint.is_some(),
+                WalletError::InvalidBatchStep
+            );
+        },
+        _ => return Err(WalletError::UnsupportedAction.into())
+    }
+    Ok(())
+}
+
 #[derive(Accounts)]
 #[instruction(proposal_id: u64, guardian_id: u64, timestamp: i64)]
 pub struct ApproveProposal<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
     pub multisig: Account<'info, MultiSigWallet>,
     
     #[account(
@@ -114,13 +242,9 @@ pub struct ApproveProposal<'info> {
     pub proposal: Account<'info, TransactionProposal>,
     
     #[account(
-        init,
+        init_if_needed,
         payer = payer,
-        space = 8 + 
-                32 +  
-                8 +  
-                8 +  
-                1,    
+        space = 8 + ProposalSignature::INIT_SPACE,
         seeds = [
             b"signature".as_ref(),
             proposal.key().as_ref(),
@@ -131,9 +255,11 @@ pub struct ApproveProposal<'info> {
     pub signature: Account<'info, ProposalSignature>,
     
     #[account(
+        mut,
         seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &guardian_id.to_le_bytes()],
         bump = guardian.bump,
-        constraint = guardian.is_active @ WalletError::InactiveGuardian
+        constraint = guardian.is_active @ WalletError::InactiveGuardian,
+        constraint = guardian.has_permission(PERMISSION_APPROVE) @ WalletError::PermissionDenied
     )]
     pub guardian: Account<'info, Guardian>,
     
@@ -149,157 +275,336 @@ pub struct ApproveProposal<'info> {
 }
 
 
-fn standardize_pubkey(pubkey: &[u8; 33]) -> [u8; 33] {
-
-    msg!("Standardizing pubkey: {}", to_hex(pubkey));
-    
-    *pubkey
-}
-
-pub fn approve_proposal(
-    ctx: Context<ApproveProposal>, 
+/// Khi `execute_if_ready` bật và phiếu này giúp đề xuất sẵn sàng (đủ ngưỡng, qua timelock),
+/// đề xuất được thực thi ngay; địa chỉ đích, tài khoản token, mint và token program
+/// được truyền qua remaining_accounts.
+pub fn approve_proposal<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ApproveProposal<'info>>, 
     proposal_id: u64,
     guardian_id: u64,
     timestamp: i64,
-    message: Vec<u8>
+    proof: GuardianProof,
+    execute_if_ready: bool
 ) -> Result<()> {
-    let multisig = &ctx.accounts.multisig;
+    let multisig = &mut ctx.accounts.multisig;
     let proposal = &mut ctx.accounts.proposal;
     let signature = &mut ctx.accounts.signature;
-    let guardian = &ctx.accounts.guardian;
+    let guardian = &mut ctx.accounts.guardian;
     let clock = &ctx.accounts.clock;
     
-    // Thêm logs để debug
-    msg!("Bắt đầu phê duyệt đề xuất với ID: {}", proposal_id);
-    msg!("Địa chỉ multisig: {}", multisig.key());
-    msg!("Guardian ID: {}", guardian_id);
-    msg!("Timestamp: {}", timestamp);
-    
     // Thêm kiểm tra chủ sở hữu của tài khoản multisig
     require!(
         *multisig.to_account_info().owner == crate::ID,
         WalletError::InvalidOwner
     );
     
+    let context = SigningContext {
+        instruction_sysvar: &ctx.accounts.instruction_sysvar,
+        multisig: multisig.key(),
+        rp_id_hash: multisig.rp_id_hash,
+        signers: signer_keys(ctx.accounts.payer.as_ref(), ctx.remaining_accounts),
+    };
+    verify_guardian_approval(&context, proposal, guardian, &proof, timestamp, clock.unix_timestamp)?;
+    
+    let weight = guardian.vote_weight();
+    record_approval(proposal, signature, guardian_id, weight, clock.unix_timestamp, ctx.bumps.signature)?;
+    
+    if !execute_if_ready {
+        return Ok(());
+    }
+    if !is_ready_to_execute(multisig, proposal, clock.unix_timestamp) {
+        msg!("Đề xuất {} chưa sẵn sàng thực thi", proposal_id);
+        return Ok(());
+    }
+    
+    let accounts = ExecutionAccounts {
+        payer: &ctx.accounts.payer,
+        destination: None,
+        vault_token_account: None,
+        destination_token_account: None,
+        token_mint: None,
+        token_program: None,
+        system_program: &ctx.accounts.system_program,
+        remaining_accounts: ctx.remaining_accounts,
+    };
+    execute_with_approver(multisig, proposal, guardian, accounts, clock.unix_timestamp)
+}
+
+/// Thực thi đề xuất ngay trong lệnh phê duyệt của `approver`. Đề xuất "config" có thể sửa hoặc
+/// xóa chính guardian này qua bản sao trong remaining_accounts, nên guardian được ghi xuống trước
+/// khi thực thi và tải lại sau đó để Anchor không ghi đè thay đổi bằng dữ liệu cũ khi kết thúc lệnh.
+fn execute_with_approver<'info>(
+    multisig: &mut Account<'info, MultiSigWallet>,
+    proposal: &mut Account<'info, TransactionProposal>,
+    approver: &mut Account<'info, Guardian>,
+    accounts: ExecutionAccounts<'_, 'info>,
+    now: i64,
+) -> Result<()> {
+    approver.exit(&crate::ID)?;
+    execute_approved_proposal(multisig, proposal, accounts, now)?;
+    
+    // Guardian đã bị xóa: Anchor bỏ qua tài khoản đã đóng khi kết thúc lệnh
+    if approver.to_account_info().data_is_empty() {
+        return Ok(());
+    }
+    approver.reload()
+}
+
+
+/// Kiểm tra guardian có thể phê duyệt đề xuất: timestamp còn hiệu lực, đề xuất chưa hết hạn
+/// và guardian ký đúng payload phê duyệt theo loại khóa của mình
+fn verify_guardian_approval(
+    context: &SigningContext,
+    proposal: &Account<TransactionProposal>,
+    guardian: &mut Guardian,
+    proof: &GuardianProof,
+    timestamp: i64,
+    now: i64,
+) -> Result<()> {
     // 1. Kiểm tra timestemp
     require!(
-        timestamp <= clock.unix_timestamp + 60, 
+        timestamp <= now + 60, 
         WalletError::FutureTimestamp
     );
     
     require!(
-        timestamp >= clock.unix_timestamp - 300,
+        timestamp >= now - 300,
         WalletError::ExpiredTimestamp
     );
     
-    if let Some(webauthn_pubkey) = guardian.webauthn_pubkey {
-        msg!("Guardian có WebAuthn public key: {}", to_hex(&webauthn_pubkey));
-        
-        let instruction_sysvar = &ctx.accounts.instruction_sysvar;
-        require!(
-            !instruction_sysvar.data_is_empty(),
-            WalletError::InstructionMissing
-        );
-        
-        let secp_ix = load_instruction_at_checked(0, instruction_sysvar)?;
-        
-        let secp256r1_verify_id = Pubkey::from_str("Secp256r1SigVerify1111111111111111111111111").unwrap();
-        require!(
-            secp_ix.program_id == secp256r1_verify_id,
-            WalletError::InvalidSignatureVerification
-        );
-        
-        let pk_in_ix = extract_public_key_from_secp_instruction(&secp_ix.data)?;
-        
-        msg!("Public key từ instruction: {}", to_hex(&pk_in_ix));
-        
-        require!(
-            pk_in_ix == webauthn_pubkey,
-            WalletError::PublicKeyMismatch
-        );
-        
-        let standardized_pubkey = standardize_pubkey(&webauthn_pubkey);
-        msg!("Standardized public key: {}", to_hex(&standardized_pubkey));
-        
-        let pubkey_hash = hash(&standardized_pubkey).to_bytes();
-        let pubkey_hash_hex = to_hex(&pubkey_hash[0..6]);
-        msg!("Public key hash after standardization: {}", pubkey_hash_hex);
-        
-        let expected_message = format!(
-            "approve:proposal_{},guardian_{},timestamp:{},pubkey:{}",
+    require!(
+        !proposal.is_expired(now),
+        WalletError::ProposalExpired
+    );
+    
+    let proposal_id = proposal.proposal_id;
+    let guardian_id = guardian.guardian_id;
+    authenticate_guardian(
+        context,
+        guardian,
+        proof,
+        timestamp,
+        SigningAction::ApproveProposal {
+            proposal: proposal.key(),
             proposal_id,
             guardian_id,
-            timestamp,
-            pubkey_hash_hex
-        );
-        
-        msg!("Expected message: {}", expected_message);
-        msg!("Received message length: {}", message.len());
-        msg!("Received message: {}", String::from_utf8_lossy(&message));
-        
-        if expected_message.as_bytes().len() == message.len() {
-            for (i, (exp, rec)) in expected_message.as_bytes().iter().zip(message.iter()).enumerate() {
-                if exp != rec {
-                    msg!("Khác biệt tại vị trí [{}]: Expected {} ({}), Received {} ({})", 
-                        i, exp, char::from(*exp), rec, char::from(*rec));
-                }
-            }
-        }
-        
-        require!(
-            message == expected_message.as_bytes(),
-            WalletError::MessageMismatch
-        );
+        },
+    )
+}
+
+/// Ghi phiếu phê duyệt của guardian, hỗ trợ đổi phiếu từ chối sang phê duyệt
+fn record_approval(
+    proposal: &mut Account<TransactionProposal>,
+    signature: &mut ProposalSignature,
+    guardian_id: u64,
+    weight: u8,
+    now: i64,
+    bump: u8,
+) -> Result<()> {
+    // Phiếu đã tồn tại nghĩa là guardian đổi phiếu từ chối sang phê duyệt
+    if signature.proposal != Pubkey::default() {
+        require!(!signature.approved, WalletError::AlreadyVoted);
+        proposal.remove_vote(false, signature.weight)?;
+        msg!("Guardian {} đổi phiếu từ từ chối sang phê duyệt", guardian_id);
     }
     
+    signature.version = ACCOUNT_VERSION;
     signature.proposal = proposal.key();
     signature.guardian_id = guardian_id;
-    signature.signature_time = clock.unix_timestamp;
-    signature.bump = ctx.bumps.signature;
+    signature.signature_time = now;
+    signature.bump = bump;
+    signature.approved = true;
+    signature.weight = weight;
     
-    proposal.signatures_count += 1;
+    proposal.add_vote(true, weight)?;
     
-    msg!("Guardian {} đã phê duyệt đề xuất {}", guardian_id, proposal_id);
+    msg!("Guardian {} đã phê duyệt đề xuất {}", guardian_id, proposal.proposal_id);
     
     Ok(())
 }
 
-
-fn extract_public_key_from_secp_instruction(data: &[u8]) -> Result<[u8; 33]> {
-    if data.len() < 16 {
-        return Err(WalletError::InvalidInstructionData.into());
-    }
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct BatchApprove<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultiSigWallet>,
     
-    let num_signatures = data[0] as usize;
-    if num_signatures != 1 {
-        return Err(WalletError::InvalidSignatureCount.into());
-    }
+    #[account(
+        mut,
+        seeds = [
+            b"proposal".as_ref(), 
+            multisig.key().as_ref(), 
+            &proposal_id.to_le_bytes()
+        ],
+        bump = proposal.bump,
+        constraint = proposal.status == ProposalStatus::Pending @ WalletError::InvalidOperation,
+        constraint = *multisig.to_account_info().key == proposal.multisig @ WalletError::MultisigMismatch
+    )]
+    pub proposal: Account<'info, TransactionProposal>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
     
-    let public_key_offset = u16::from_le_bytes([data[6], data[7]]) as usize;
+    /// CHECK: Địa chỉ đích, chỉ cần khi thực thi đề xuất "transfer"; được kiểm tra khi thực thi
+    #[account(mut)]
+    pub destination: Option<UncheckedAccount<'info>>,
     
-    let mut pk = [0u8; 33];
-    if data.len() < public_key_offset + 33 {
-        return Err(WalletError::InvalidInstructionData.into());
-    }
+    #[account(mut)]
+    pub vault_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     
-    pk.copy_from_slice(&data[public_key_offset..public_key_offset + 33]);
-    Ok(pk)
+    #[account(mut)]
+    pub destination_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    pub token_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    
+    /// CHECK: Đây là tài khoản sysvar chứa thông tin về các instruction trong transaction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+    
+    pub clock: Sysvar<'info, Clock>,
+    pub system_program: Program<'info, System>,
 }
 
-
-fn to_hex(bytes: &[u8]) -> String {
-    let mut result = String::with_capacity(bytes.len() * 2);
-    for byte in bytes {
-        let hex = format!("{:02x}", byte);
-        result.push_str(&hex);
+/// Ghi nhiều phiếu phê duyệt trong một giao dịch. Mỗi phê duyệt ứng với một cặp
+/// [guardian, signature PDA] (đều writable) ở đầu remaining_accounts, theo đúng thứ tự
+/// của `approvals`; các tài khoản còn lại dùng khi thực thi. Nếu `execute` bật và đề xuất
+/// đã sẵn sàng (đủ ngưỡng, qua timelock) thì thực thi luôn trong cùng giao dịch.
+pub fn batch_approve<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchApprove<'info>>,
+    proposal_id: u64,
+    approvals: Vec<GuardianApproval>,
+    execute: bool,
+) -> Result<()> {
+    let multisig = &mut ctx.accounts.multisig;
+    let proposal = &mut ctx.accounts.proposal;
+    let clock = &ctx.accounts.clock;
+    
+    require!(
+        *multisig.to_account_info().owner == crate::ID,
+        WalletError::InvalidOwner
+    );
+    require!(!approvals.is_empty(), WalletError::InvalidOperation);
+    require!(approvals.len() <= MAX_GUARDIANS as usize, WalletError::LimitExceeded);
+    
+    let approval_accounts_len = approvals.len() * 2;
+    require!(
+        ctx.remaining_accounts.len() >= approval_accounts_len,
+        WalletError::InvalidOperation
+    );
+    let (approval_accounts, execution_accounts) = ctx.remaining_accounts.split_at(approval_accounts_len);
+    
+    msg!("Ghi {} phê duyệt cho đề xuất {}", approvals.len(), proposal_id);
+    
+    let multisig_key = multisig.key();
+    let proposal_key = proposal.key();
+    
+    let context = SigningContext {
+        instruction_sysvar: &ctx.accounts.instruction_sysvar,
+        multisig: multisig_key,
+        rp_id_hash: multisig.rp_id_hash,
+        signers: signer_keys(ctx.accounts.payer.as_ref(), ctx.remaining_accounts),
+    };
+    
+    for (approval, accounts) in approvals.iter().zip(approval_accounts.chunks(2)) {
+        let guardian_info = &accounts[0];
+        let signature_info = &accounts[1];
+        require!(guardian_info.is_writable, WalletError::InvalidGuardian);
+        
+        let mut guardian = load_guardian(&multisig_key, approval.guardian_id, &accounts[..1])?;
+        require!(guardian.is_active, WalletError::InactiveGuardian);
+        require!(guardian.has_permission(PERMISSION_APPROVE), WalletError::PermissionDenied);
+        
+        verify_guardian_approval(
+            &context,
+            proposal,
+            &mut guardian,
+            &approval.proof,
+            approval.timestamp,
+            clock.unix_timestamp,
+        )?;
+        guardian.exit(&crate::ID)?;
+        let weight = guardian.vote_weight();
+        
+        let guardian_id_bytes = approval.guardian_id.to_le_bytes();
+        let (signature_pda, bump) = Pubkey::find_program_address(
+            &[b"signature".as_ref(), proposal_key.as_ref(), &guardian_id_bytes],
+            &crate::ID,
+        );
+        require!(signature_info.key() == signature_pda, WalletError::InvalidOperation);
+        
+        if signature_info.data_is_empty() {
+            let seeds = &[
+                b"signature".as_ref(),
+                proposal_key.as_ref(),
+                &guardian_id_bytes,
+                &[bump]
+            ];
+            create_program_account(
+                &ctx.accounts.payer,
+                signature_info,
+                &ctx.accounts.system_program,
+                8 + ProposalSignature::INIT_SPACE,
+                &[&seeds[..]],
+            )?;
+            
+            let mut signature = ProposalSignature {
+                version: ACCOUNT_VERSION,
+                proposal: Pubkey::default(),
+                guardian_id: approval.guardian_id,
+                signature_time: 0,
+                bump,
+                approved: false,
+                weight: 0,
+                reserved: [0; SIGNATURE_RESERVED_LEN],
+            };
+            record_approval(proposal, &mut signature, approval.guardian_id, weight, clock.unix_timestamp, bump)?;
+            
+            let mut data = signature_info.try_borrow_mut_data()?;
+            signature.try_serialize(&mut &mut data[..])?;
+        } else {
+            let mut signature = Account::<ProposalSignature>::try_from(signature_info)?;
+            record_approval(proposal, &mut signature, approval.guardian_id, weight, clock.unix_timestamp, bump)?;
+            signature.exit(&crate::ID)?;
+        }
     }
-    result
+    
+    msg!("Số chữ ký hiện tại: {}/{}", proposal.signatures_count, proposal.required_signatures);
+    
+    if !execute {
+        return Ok(());
+    }
+    if !is_ready_to_execute(multisig, proposal, clock.unix_timestamp) {
+        msg!("Đề xuất {} chưa sẵn sàng thực thi", proposal_id);
+        return Ok(());
+    }
+    
+    let accounts = ExecutionAccounts {
+        payer: &ctx.accounts.payer,
+        destination: ctx.accounts.destination.as_deref(),
+        vault_token_account: ctx.accounts.vault_token_account.as_deref(),
+        destination_token_account: ctx.accounts.destination_token_account.as_deref(),
+        token_mint: ctx.accounts.token_mint.as_deref(),
+        token_program: ctx.accounts.token_program.as_ref(),
+        system_program: &ctx.accounts.system_program,
+        remaining_accounts: execution_accounts,
+    };
+    execute_approved_proposal(multisig, proposal, accounts, clock.unix_timestamp)
 }
 
 #[derive(Accounts)]
 #[instruction(proposal_id: u64)]
 pub struct ExecuteProposal<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
     pub multisig: Account<'info, MultiSigWallet>,
     
     #[account(
@@ -311,7 +616,7 @@ pub struct ExecuteProposal<'info> {
         ],
         bump = proposal.bump,
         constraint = proposal.status == ProposalStatus::Pending @ WalletError::InvalidOperation,
-        constraint = proposal.signatures_count >= proposal.required_signatures @ WalletError::InvalidOperation,
+        constraint = proposal.has_quorum() @ WalletError::InvalidOperation,
         constraint = *multisig.to_account_info().key == proposal.multisig @ WalletError::MultisigMismatch
     )]
     pub proposal: Account<'info, TransactionProposal>,
@@ -323,17 +628,26 @@ pub struct ExecuteProposal<'info> {
     #[account(mut)]
     pub destination: AccountInfo<'info>,
     
+    /// Tài khoản token (ATA) của ví multisig, chỉ dùng cho token_transfer
+    #[account(mut)]
+    pub vault_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    /// Tài khoản token nhận, phải thuộc sở hữu của địa chỉ đích
+    #[account(mut)]
+    pub destination_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    pub token_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    
     pub clock: Sysvar<'info, Clock>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn execute_proposal(
-    ctx: Context<ExecuteProposal>,
+pub fn execute_proposal<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>,
     proposal_id: u64
 ) -> Result<()> {
-    let multisig_credential_id = ctx.accounts.multisig.credential_id.clone();
-    let multisig_bump = ctx.accounts.multisig.bump;
-    
     let multisig = &mut ctx.accounts.multisig;
     let proposal = &mut ctx.accounts.proposal;
     let clock = &ctx.accounts.clock;
@@ -343,19 +657,81 @@ pub fn execute_proposal(
         WalletError::InvalidOwner
     );
     
+    msg!("Thực thi đề xuất với ID: {}", proposal_id);
+    
+    let accounts = ExecutionAccounts {
+        payer: &ctx.accounts.payer,
+        destination: Some(&ctx.accounts.destination),
+        vault_token_account: ctx.accounts.vault_token_account.as_deref(),
+        destination_token_account: ctx.accounts.destination_token_account.as_deref(),
+        token_mint: ctx.accounts.token_mint.as_deref(),
+        token_program: ctx.accounts.token_program.as_ref(),
+        system_program: &ctx.accounts.system_program,
+        remaining_accounts: ctx.remaining_accounts,
+    };
+    execute_approved_proposal(multisig, proposal, accounts, clock.unix_timestamp)
+}
+
+/// Các tài khoản cần để thực thi một đề xuất đã đủ phê duyệt. Những tài khoản không
+/// dùng cho loại hành động của đề xuất có thể để `None`; với "transfer" và "token_transfer",
+/// nếu không có tài khoản tường minh thì chúng được tìm trong remaining_accounts.
+pub struct ExecutionAccounts<'a, 'info> {
+    pub payer: &'a Signer<'info>,
+    pub destination: Option<&'a AccountInfo<'info>>,
+    pub vault_token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub destination_token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub token_mint: Option<&'a InterfaceAccount<'info, Mint>>,
+    pub token_program: Option<&'a Interface<'info, TokenInterface>>,
+    pub system_program: &'a Program<'info, System>,
+    pub remaining_accounts: &'info [AccountInfo<'info>],
+}
+
+/// Đề xuất đã đủ chữ ký, chưa hết hạn và đã qua timelock
+fn is_ready_to_execute(multisig: &MultiSigWallet, proposal: &TransactionProposal, now: i64) -> bool {
+    let unlock_at = proposal.created_at
+        .saturating_add(multisig.timelock.delay_for(proposal.total_spend()));
+    
+    proposal.status == ProposalStatus::Pending
+        && proposal.has_quorum()
+        && !proposal.is_expired(now)
+        && now >= unlock_at
+}
+
+fn execute_approved_proposal<'info>(
+    multisig: &mut Account<'info, MultiSigWallet>,
+    proposal: &mut Account<'info, TransactionProposal>,
+    accounts: ExecutionAccounts<'_, 'info>,
+    now: i64,
+) -> Result<()> {
+    require!(proposal.has_quorum(), WalletError::InvalidOperation);
+    
     require!(
-        proposal.signatures_count >= proposal.required_signatures,
-        WalletError::InvalidOperation
+        !proposal.is_expired(now),
+        WalletError::ProposalExpired
+    );
+    
+    let unlock_at = proposal.created_at
+        .saturating_add(multisig.timelock.delay_for(proposal.total_spend()));
+    require!(
+        now >= unlock_at,
+        WalletError::TimelockActive
     );
     
-    msg!("Thực thi đề xuất với ID: {}", proposal_id);
-    msg!("Địa chỉ multisig: {}", multisig.key());
     msg!("Số chữ ký hiện tại: {}/{}", proposal.signatures_count, proposal.required_signatures);
     
 
     match proposal.action.as_str() {
+        "transfer" | "token_transfer" 
+            if accounts.destination.is_none() && accounts.vault_token_account.is_none() => {
+            let step = ProposalAction {
+                action: proposal.action.clone(),
+                params: proposal.params.clone(),
+            };
+            execute_batch_step(multisig, &step, accounts.remaining_accounts)?;
+        },
         "transfer" => {
-            let destination = ctx.accounts.destination.key();
+            let destination_info = accounts.destination.ok_or(WalletError::InvalidOperation)?;
+            let destination = destination_info.key();
             let params_destination = proposal.params.destination.ok_or(WalletError::InvalidOperation)?;
             
             require!(
@@ -366,15 +742,9 @@ pub fn execute_proposal(
             let amount = proposal.params.amount.ok_or(WalletError::InvalidOperation)?;
             
             let multisig_info = multisig.to_account_info();
-            let credential_id_bytes = process_credential_id_seed(&multisig_credential_id);
-            let _seeds = &[
-                b"multisig".as_ref(),
-                &credential_id_bytes,
-                &[multisig_bump]
-            ];
             
-            let dest_starting_lamports = ctx.accounts.destination.lamports();
-            **ctx.accounts.destination.lamports.borrow_mut() = dest_starting_lamports
+            let dest_starting_lamports = destination_info.lamports();
+            **destination_info.lamports.borrow_mut() = dest_starting_lamports
                 .checked_add(amount)
                 .ok_or(WalletError::ArithmeticOverflow)?;
             
@@ -385,24 +755,400 @@ pub fn execute_proposal(
             
             msg!("Đã chuyển {} SOL đến {}", amount as f64 / 1_000_000_000.0, destination);
         },
+        "token_transfer" => {
+            let vault_token_account = accounts.vault_token_account.ok_or(WalletError::TokenAccountMissing)?;
+            let destination_token_account = accounts.destination_token_account.ok_or(WalletError::TokenAccountMissing)?;
+            let token_mint = accounts.token_mint.ok_or(WalletError::TokenAccountMissing)?;
+            let token_program = accounts.token_program.ok_or(WalletError::TokenAccountMissing)?;
+            
+            transfer_tokens_from_vault(
+                multisig,
+                vault_token_account,
+                destination_token_account,
+                token_mint,
+                token_program,
+                &proposal.params,
+            )?;
+        },
+        "invoke" => {
+            execute_proposal_instructions(multisig, &proposal.instructions, accounts.remaining_accounts)?;
+        },
+        "batch" => {
+            execute_batch(multisig, &proposal.actions, accounts.remaining_accounts)?;
+        },
+        "config" => {
+            let config_action = proposal.config_action.clone().ok_or(WalletError::InvalidOperation)?;
+            apply_config_action(
+                multisig,
+                config_action,
+                accounts.remaining_accounts,
+                accounts.payer,
+                accounts.system_program,
+                now,
+            )?;
+        },
         _ => return Err(WalletError::UnsupportedAction.into())
     }
     
     proposal.status = ProposalStatus::Executed;
-    proposal.executed_at = Some(clock.unix_timestamp);
+    proposal.executed_at = Some(now);
+    multisig.settle_proposal();
     
     multisig.transaction_nonce += 1;
-    multisig.last_transaction_timestamp = clock.unix_timestamp;
+    multisig.last_transaction_timestamp = now;
+    
+    msg!("Đã thực thi đề xuất {} thành công", proposal.proposal_id);
+    
+    Ok(())
+}
+
+/// Gọi lần lượt các instruction của đề xuất, ký bằng seeds của PDA multisig.
+/// Mọi tài khoản (kể cả program) phải được truyền qua remaining_accounts, trừ chính ví multisig.
+fn execute_proposal_instructions<'info>(
+    multisig: &Account<'info, MultiSigWallet>,
+    instructions: &[ProposalInstruction],
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    let multisig_key = multisig.key();
+    let address_seed = multisig_address_seed(multisig);
+    let seeds = &[
+        b"multisig".as_ref(),
+        &address_seed,
+        &[multisig.bump]
+    ];
+    let signer_seeds = &[&seeds[..]];
+    
+    let find_account = |pubkey: &Pubkey| -> Result<AccountInfo<'info>> {
+        if *pubkey == multisig_key {
+            return Ok(multisig.to_account_info());
+        }
+        find_remaining_account(remaining_accounts, pubkey).cloned()
+    };
+    
+    for (i, ix) in instructions.iter().enumerate() {
+        require!(ix.program_id != crate::ID, WalletError::InvalidCpiProgram);
+        
+        let mut account_infos = Vec::with_capacity(ix.accounts.len() + 1);
+        let mut metas = Vec::with_capacity(ix.accounts.len());
+        for meta in ix.accounts.iter() {
+            account_infos.push(find_account(&meta.pubkey)?);
+            metas.push(AccountMeta {
+                pubkey: meta.pubkey,
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            });
+        }
+        account_infos.push(find_account(&ix.program_id)?);
+        
+        let instruction = Instruction {
+            program_id: ix.program_id,
+            accounts: metas,
+            data: ix.data.clone(),
+        };
+        
+        msg!("Gọi instruction {} tới program {}", i, ix.program_id);
+        invoke_signed(&instruction, &account_infos, signer_seeds)?;
+    }
+    
+    Ok(())
+}
+
+/// Thực thi các bước của đề xuất "batch" theo thứ tự. Lỗi ở bất kỳ bước nào
+/// sẽ hoàn tác toàn bộ giao dịch; mã lỗi trả về cho biết bước nào thất bại, lỗi gốc được ghi vào log.
+fn execute_batch<'info>(
+    multisig: &Account<'info, MultiSigWallet>,
+    actions: &[ProposalAction],
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    for (i, step) in actions.iter().enumerate() {
+        msg!("Thực thi bước {}: {}", i, step.action);
+        execute_batch_step(multisig, step, remaining_accounts).map_err(|err| {
+            msg!("Bước {} ({}) thất bại: {}", i, step.action, err);
+            error!(WalletError::batch_step_failed(i))
+        })?;
+    }
+    
+    msg!("Đã thực thi {} bước của batch", actions.len());
+    Ok(())
+}
+
+fn execute_batch_step<'info>(
+    multisig: &Account<'info, MultiSigWallet>,
+    step: &ProposalAction,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    let params = &step.params;
+    
+    match step.action.as_str() {
+        "transfer" => {
+            let amount = params.amount.ok_or(WalletError::InvalidBatchStep)?;
+            let destination = params.destination.ok_or(WalletError::InvalidBatchStep)?;
+            let destination_info = find_remaining_account(remaining_accounts, &destination)?;
+            let multisig_info = multisig.to_account_info();
+            
+            let dest_starting_lamports = destination_info.lamports();
+            **destination_info.lamports.borrow_mut() = dest_starting_lamports
+                .checked_add(amount)
+                .ok_or(WalletError::ArithmeticOverflow)?;
+            
+            let multisig_starting_lamports = multisig_info.lamports();
+            **multisig_info.lamports.borrow_mut() = multisig_starting_lamports
+                .checked_sub(amount)
+                .ok_or(WalletError::InsufficientFunds)?;
+            
+            msg!("Đã chuyển {} SOL đến {}", amount as f64 / 1_000_000_000.0, destination);
+        },
+        "token_transfer" => {
+            let destination = params.destination.ok_or(WalletError::InvalidBatchStep)?;
+            let mint_key = params.token_mint.ok_or(WalletError::InvalidBatchStep)?;
+            
+            let mint_info = find_remaining_account(remaining_accounts, &mint_key)?;
+            let token_program_info = find_remaining_account(remaining_accounts, mint_info.owner)?;
+            let token_program_id = token_program_info.key();
+            
+            let vault_info = find_remaining_account(
+                remaining_accounts,
+                &get_associated_token_address_with_program_id(&multisig.key(), &mint_key, &token_program_id),
+            )?;
+            let destination_token_info = find_remaining_account(
+                remaining_accounts,
+                &get_associated_token_address_with_program_id(&destination, &mint_key, &token_program_id),
+            )?;
+            
+            transfer_tokens_from_vault(
+                multisig,
+                &InterfaceAccount::<TokenAccount>::try_from(vault_info)?,
+                &InterfaceAccount::<TokenAccount>::try_from(destination_token_info)?,
+                &InterfaceAccount::<Mint>::try_from(mint_info)?,
+                &Interface::<TokenInterface>::try_from(token_program_info)?,
+                params,
+            )?;
+        },
+        "close_token_account" => {
+            let destination = params.destination.ok_or(WalletError::InvalidBatchStep)?;
+            let mint_key = params.token_mint.ok_or(WalletError::InvalidBatchStep)?;
+            
+            let mint_info = find_remaining_account(remaining_accounts, &mint_key)?;
+            let token_program_info = find_remaining_account(remaining_accounts, mint_info.owner)?;
+            let token_program = Interface::<TokenInterface>::try_from(token_program_info)?;
+            
+            let vault_info = find_remaining_account(
+                remaining_accounts,
+                &get_associated_token_address_with_program_id(&multisig.key(), &mint_key, &token_program.key()),
+            )?;
+            let destination_info = find_remaining_account(remaining_accounts, &destination)?;
+            
+            let address_seed = multisig_address_seed(multisig);
+            let seeds = &[
+                b"multisig".as_ref(),
+                &address_seed,
+                &[multisig.bump]
+            ];
+            
+            token_interface::close_account(CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                CloseAccount {
+                    account: vault_info.clone(),
+                    destination: destination_info.clone(),
+                    authority: multisig.to_account_info(),
+                },
+                &[&seeds[..]],
+            ))?;
+            
+            msg!("Đã đóng tài khoản token {} của ví, rent chuyển về {}", vault_info.key(), destination);
+        },
+        _ => return Err(WalletError::UnsupportedAction.into())
+    }
+    
+    Ok(())
+}
+
+/// Áp dụng thay đổi cấu hình của đề xuất "config". Các guardian bị tác động
+/// phải được truyền qua remaining_accounts (writable).
+fn apply_config_action<'info>(
+    multisig: &mut Account<'info, MultiSigWallet>,
+    config_action: ConfigAction,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    now: i64,
+) -> Result<()> {
+    config_action.validate()?;
+    let multisig_key = multisig.key();
+    let changes_guardians = !matches!(
+        config_action,
+        ConfigAction::SetTimelock(_) | ConfigAction::SetSpendingPolicy(_) | ConfigAction::SetGuardianSpendingLimits { .. }
+    );
     
-    msg!("Đã thực thi đề xuất {} thành công", proposal_id);
+    match config_action {
+        ConfigAction::SetTimelock(timelock) => {
+            msg!("Cập nhật timelock: {} giây, {} mức", timelock.delay, timelock.tiers.len());
+            multisig.timelock = timelock;
+        },
+        ConfigAction::SetSpendingPolicy(mut policy) => {
+            carry_over_spending(&mut policy.limits, &multisig.spending_policy.limits, now);
+            msg!("Cập nhật chính sách chi tiêu: {} hạn mức, {} địa chỉ cho phép", policy.limits.len(), policy.allowlist.len());
+            multisig.spending_policy = policy;
+        },
+        ConfigAction::AddGuardian { guardian_id, name, recovery_hash, is_owner, auth, permissions, weight } => {
+            let (guardian_pda, _) = Pubkey::find_program_address(
+                &[b"guardian".as_ref(), multisig_key.as_ref(), &guardian_id.to_le_bytes()],
+                &crate::ID,
+            );
+            let guardian_info = find_remaining_account(remaining_accounts, &guardian_pda)?;
+            create_guardian_account(
+                multisig,
+                guardian_info,
+                payer,
+                system_program,
+                guardian_id,
+                name,
+                recovery_hash,
+                is_owner,
+                auth,
+                permissions,
+                weight,
+            )?;
+        },
+        ConfigAction::RemoveGuardian { guardian_id } => {
+            let guardian = load_guardian(&multisig_key, guardian_id, remaining_accounts)?;
+            require!(multisig.guardian_count > 0, WalletError::NoGuardians);
+            // PDA guardian có thể được tạo lại với cùng id, thiết bị cũ không được sống lại
+            require!(guardian.device_count == 0, WalletError::DevicesStillRegistered);
+            
+            if guardian.is_active {
+                multisig.active_guardian_count -= 1;
+                if guardian.is_owner {
+                    multisig.owner_count -= 1;
+                }
+            }
+            multisig.update_approvers(guardian.approver_weight(), 0);
+            multisig.remove_guardian_entry(guardian_id);
+            
+            // Rent của guardian được trả về ví multisig
+            guardian.close(multisig.to_account_info())?;
+            multisig.guardian_count -= 1;
+            msg!("Guardian {} đã bị xóa qua đề xuất", guardian_id);
+        },
+        ConfigAction::SetGuardianStatus { guardian_id, is_active } => {
+            let mut guardian = load_guardian(&multisig_key, guardian_id, remaining_accounts)?;
+            if guardian.is_active != is_active {
+                if is_active {
+                    multisig.active_guardian_count += 1;
+                    if guardian.is_owner {
+                        multisig.owner_count += 1;
+                    }
+                } else {
+                    multisig.active_guardian_count -= 1;
+                    if guardian.is_owner {
+                        multisig.owner_count -= 1;
+                    }
+                }
+            }
+            let was_weight = guardian.approver_weight();
+            guardian.is_active = is_active;
+            multisig.update_approvers(was_weight, guardian.approver_weight());
+            multisig.sync_guardian_entry(&guardian)?;
+            guardian.exit(&crate::ID)?;
+            msg!("Trạng thái guardian {}: {}", guardian_id, is_active);
+        },
+        ConfigAction::SetGuardianOwner { guardian_id, is_owner } => {
+            let mut guardian = load_guardian(&multisig_key, guardian_id, remaining_accounts)?;
+            if is_owner {
+                require!(guardian.passkey().is_some(), WalletError::WebAuthnNotConfigured);
+            }
+            if guardian.is_active && guardian.is_owner != is_owner {
+                if is_owner {
+                    multisig.owner_count += 1;
+                } else {
+                    multisig.owner_count -= 1;
+                }
+            }
+            if guardian.is_owner && !is_owner {
+                guardian.revoke_devices();
+            }
+            guardian.is_owner = is_owner;
+            multisig.sync_guardian_entry(&guardian)?;
+            guardian.exit(&crate::ID)?;
+            msg!("Quyền owner của guardian {}: {}", guardian_id, is_owner);
+        },
+        ConfigAction::ChangeThreshold { threshold } => {
+            msg!("Đổi ngưỡng từ {} thành {}", multisig.threshold, threshold);
+            multisig.threshold = threshold;
+        },
+        ConfigAction::SetRpIdHash { rp_id_hash } => {
+            multisig.rp_id_hash = rp_id_hash;
+            msg!("Đã cập nhật rpIdHash của ví");
+        },
+        ConfigAction::MigrateSeeds { salt } => {
+            migrate_wallet_seeds(multisig, salt, remaining_accounts, payer, system_program)?;
+        },
+        ConfigAction::SetGuardianWeight { guardian_id, weight } => {
+            let mut guardian = load_guardian(&multisig_key, guardian_id, remaining_accounts)?;
+            let was_weight = guardian.approver_weight();
+            guardian.weight = weight;
+            multisig.update_approvers(was_weight, guardian.approver_weight());
+            guardian.exit(&crate::ID)?;
+            msg!("Trọng số của guardian {}: {}", guardian_id, weight);
+        },
+        ConfigAction::SetWeightedVoting { enabled } => {
+            // Tính lại tổng trọng số từ mọi guardian của ví (truyền qua remaining_accounts)
+            if enabled {
+                let mut approver_weight: u16 = 0;
+                for entry in multisig.guardians.iter() {
+                    let guardian = load_guardian(&multisig_key, entry.guardian_id, remaining_accounts)?;
+                    approver_weight = approver_weight
+                        .checked_add(guardian.approver_weight())
+                        .ok_or(WalletError::ArithmeticOverflow)?;
+                }
+                multisig.approver_weight = approver_weight;
+            }
+            multisig.weighted_voting = enabled;
+            msg!("Bỏ phiếu theo trọng số: {}", enabled);
+        },
+        ConfigAction::SetGuardianPermissions { guardian_id, permissions } => {
+            let mut guardian = load_guardian(&multisig_key, guardian_id, remaining_accounts)?;
+            let was_weight = guardian.approver_weight();
+            guardian.permissions = permissions;
+            multisig.update_approvers(was_weight, guardian.approver_weight());
+            multisig.sync_guardian_entry(&guardian)?;
+            guardian.exit(&crate::ID)?;
+            msg!("Quyền của guardian {}: {:#06b}", guardian_id, permissions);
+        },
+        ConfigAction::SetGuardianSpendingLimits { guardian_id, mut limits } => {
+            let mut guardian = load_guardian(&multisig_key, guardian_id, remaining_accounts)?;
+            carry_over_spending(&mut limits, &guardian.spending_limits, now);
+            msg!("Guardian {}: {} hạn mức chi tiêu", guardian_id, limits.len());
+            guardian.spending_limits = limits;
+            guardian.exit(&crate::ID)?;
+        },
+    }
+    
+    if changes_guardians {
+        multisig.check_invariants()?;
+    }
     
     Ok(())
 }
 
+fn find_remaining_account<'info>(
+    remaining_accounts: &'info [AccountInfo<'info>],
+    pubkey: &Pubkey,
+) -> Result<&'info AccountInfo<'info>> {
+    remaining_accounts
+        .iter()
+        .find(|account| account.key == pubkey)
+        .ok_or(WalletError::CpiAccountMissing.into())
+}
+
 #[derive(Accounts)]
 #[instruction(proposal_id: u64, guardian_id: u64, timestamp: i64)]
 pub struct RejectProposal<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
     pub multisig: Account<'info, MultiSigWallet>,
     
     #[account(
@@ -419,9 +1165,24 @@ pub struct RejectProposal<'info> {
     pub proposal: Account<'info, TransactionProposal>,
     
     #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ProposalSignature::INIT_SPACE,
+        seeds = [
+            b"signature".as_ref(),
+            proposal.key().as_ref(),
+            &guardian_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub signature: Account<'info, ProposalSignature>,
+    
+    #[account(
+        mut,
         seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &guardian_id.to_le_bytes()],
         bump = guardian.bump,
-        constraint = guardian.is_active @ WalletError::InactiveGuardian
+        constraint = guardian.is_active @ WalletError::InactiveGuardian,
+        constraint = guardian.has_permission(PERMISSION_APPROVE) @ WalletError::PermissionDenied
     )]
     pub guardian: Account<'info, Guardian>,
     
@@ -433,6 +1194,7 @@ pub struct RejectProposal<'info> {
     pub instruction_sysvar: AccountInfo<'info>,
     
     pub clock: Sysvar<'info, Clock>,
+    pub system_program: Program<'info, System>,
 }
 
 pub fn reject_proposal(
@@ -440,18 +1202,14 @@ pub fn reject_proposal(
     proposal_id: u64,
     guardian_id: u64,
     timestamp: i64,
-    message: Vec<u8>
+    proof: GuardianProof
 ) -> Result<()> {
-    let multisig = &ctx.accounts.multisig;
+    let multisig = &mut ctx.accounts.multisig;
     let proposal = &mut ctx.accounts.proposal;
-    let guardian = &ctx.accounts.guardian;
+    let signature = &mut ctx.accounts.signature;
+    let guardian = &mut ctx.accounts.guardian;
     let clock = &ctx.accounts.clock;
     
-    msg!("Bắt đầu từ chối đề xuất với ID: {}", proposal_id);
-    msg!("Địa chỉ multisig: {}", multisig.key());
-    msg!("Guardian ID: {}", guardian_id);
-    msg!("Timestamp: {}", timestamp);
-    
     require!(
         *multisig.to_account_info().owner == crate::ID,
         WalletError::InvalidOwner
@@ -467,65 +1225,427 @@ pub fn reject_proposal(
         WalletError::ExpiredTimestamp
     );
     
-    if let Some(webauthn_pubkey) = guardian.webauthn_pubkey {
-        msg!("Guardian có WebAuthn public key: {}", to_hex(&webauthn_pubkey));
+    let context = SigningContext {
+        instruction_sysvar: &ctx.accounts.instruction_sysvar,
+        multisig: multisig.key(),
+        rp_id_hash: multisig.rp_id_hash,
+        signers: signer_keys(ctx.accounts.payer.as_ref(), ctx.remaining_accounts),
+    };
+    authenticate_guardian(
+        &context,
+        guardian,
+        &proof,
+        timestamp,
+        SigningAction::RejectProposal {
+            proposal: proposal.key(),
+            proposal_id,
+            guardian_id,
+        },
+    )?;
+    
+    // Đề xuất đã đủ ngưỡng và đang trong thời gian khóa: một guardian có quyền phủ quyết
+    let unlock_at = proposal.created_at
+        .saturating_add(multisig.timelock.delay_for(proposal.total_spend()));
+    let vetoed = proposal.has_quorum() && clock.unix_timestamp < unlock_at;
+    
+    // Phiếu đã tồn tại nghĩa là guardian đổi phiếu phê duyệt sang từ chối
+    if signature.proposal != Pubkey::default() {
+        require!(signature.approved, WalletError::AlreadyVoted);
+        proposal.remove_vote(true, signature.weight)?;
+        msg!("Guardian {} đổi phiếu từ phê duyệt sang từ chối", guardian_id);
+    }
+    
+    signature.proposal = proposal.key();
+    signature.guardian_id = guardian_id;
+    signature.signature_time = clock.unix_timestamp;
+    signature.bump = ctx.bumps.signature;
+    signature.approved = false;
+    signature.weight = guardian.vote_weight();
+    signature.version = ACCOUNT_VERSION;
+    
+    proposal.add_vote(false, signature.weight)?;
+    
+    msg!("Guardian {} đã từ chối đề xuất {} ({} phiếu từ chối)", guardian_id, proposal_id, proposal.rejections_count);
+    
+    if vetoed {
+        proposal.status = ProposalStatus::Cancelled;
+        multisig.settle_proposal();
+        msg!("Đề xuất {} đã bị guardian {} phủ quyết trong thời gian khóa", proposal_id, guardian_id);
+        return Ok(());
+    }
+    
+    // Chỉ từ chối khi số guardian có quyền phê duyệt (hoặc tổng trọng số) còn lại không thể đạt ngưỡng phê duyệt
+    if !proposal.can_reach_quorum() {
+        proposal.status = ProposalStatus::Rejected;
+        multisig.settle_proposal();
+        msg!("Đề xuất {} đã bị từ chối do không thể đạt ngưỡng {}", proposal_id, proposal.required_signatures);
+    }
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct CancelProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultiSigWallet>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"proposal".as_ref(), 
+            multisig.key().as_ref(), 
+            &proposal_id.to_le_bytes()
+        ],
+        bump = proposal.bump,
+        constraint = proposal.status == ProposalStatus::Pending @ WalletError::InvalidOperation,
+        constraint = proposal.proposer == proposer.key() @ WalletError::InvalidOperation
+    )]
+    pub proposal: Account<'info, TransactionProposal>,
+    
+    pub proposer: Signer<'info>,
+}
+
+pub fn cancel_proposal(
+    ctx: Context<CancelProposal>,
+    proposal_id: u64
+) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    
+    proposal.status = ProposalStatus::Cancelled;
+    ctx.accounts.multisig.settle_proposal();
+    
+    msg!("Đề xuất {} đã bị hủy bởi người tạo", proposal_id);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ExpireProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultiSigWallet>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"proposal".as_ref(), 
+            multisig.key().as_ref(), 
+            &proposal_id.to_le_bytes()
+        ],
+        bump = proposal.bump,
+        constraint = proposal.status == ProposalStatus::Pending @ WalletError::InvalidOperation
+    )]
+    pub proposal: Account<'info, TransactionProposal>,
+    
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Đánh dấu Expired cho đề xuất Pending đã quá hạn. Ai cũng có thể gọi,
+/// vì phê duyệt/thực thi/từ chối đề xuất quá hạn đều bị hoàn tác và không ghi được trạng thái.
+pub fn expire_proposal(
+    ctx: Context<ExpireProposal>,
+    proposal_id: u64
+) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    
+    require!(
+        proposal.is_expired(ctx.accounts.clock.unix_timestamp),
+        WalletError::ProposalNotFinalized
+    );
+    
+    proposal.status = ProposalStatus::Expired;
+    ctx.accounts.multisig.settle_proposal();
+    
+    msg!("Đề xuất {} đã hết hạn", proposal_id);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct CloseProposal<'info> {
+    #[account(
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultiSigWallet>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"proposal".as_ref(), 
+            multisig.key().as_ref(), 
+            &proposal_id.to_le_bytes()
+        ],
+        bump = proposal.bump,
+        close = rent_receiver
+    )]
+    pub proposal: Account<'info, TransactionProposal>,
+    
+    /// CHECK: Người tạo đề xuất, nhận lại rent của đề xuất và các chữ ký
+    #[account(mut, address = proposal.proposer @ WalletError::InvalidOperation)]
+    pub rent_receiver: AccountInfo<'info>,
+    
+    pub payer: Signer<'info>,
+    
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Đóng đề xuất đã Executed/Rejected/Expired/Cancelled (hoặc Pending đã quá hạn).
+/// Toàn bộ phiếu (ProposalSignature) của đề xuất phải được truyền qua remaining_accounts.
+pub fn close_proposal<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CloseProposal<'info>>,
+    proposal_id: u64
+) -> Result<()> {
+    let proposal = &ctx.accounts.proposal;
+    let clock = &ctx.accounts.clock;
+    let proposal_key = proposal.key();
+    
+    let finalized = match proposal.status {
+        ProposalStatus::Pending => proposal.is_expired(clock.unix_timestamp),
+        _ => true,
+    };
+    require!(finalized, WalletError::ProposalNotFinalized);
+    
+    require!(
+        ctx.remaining_accounts.len() == proposal.signatures_count as usize + proposal.rejections_count as usize,
+        WalletError::InvalidSignatureCount
+    );
+    
+    let rent_receiver = ctx.accounts.rent_receiver.to_account_info();
+    for info in ctx.remaining_accounts.iter() {
+        let signature = Account::<ProposalSignature>::try_from(info)?;
+        require!(signature.proposal == proposal_key, WalletError::InvalidOperation);
+        signature.close(rent_receiver.clone())?;
+    }
+    
+    msg!("Đã đóng đề xuất {} và {} chữ ký", proposal_id, ctx.remaining_accounts.len());
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn account_data<T: AccountSerialize>(account: &T) -> Vec<u8> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data
+    }
+    
+    /// AccountInfo sống suốt test với bố cục như runtime cấp: 8 byte độ dài trước dữ liệu
+    /// và 4 byte độ dài ban đầu trước khóa, để `close` (resize về 0) hoạt động như on-chain
+    fn leaked_account(key: Pubkey, owner: Pubkey, data: Vec<u8>, is_signer: bool, executable: bool) -> AccountInfo<'static> {
+        #[repr(C)]
+        struct KeySlot {
+            original_data_len: u32,
+            key: Pubkey,
+        }
+        let slot = Box::leak(Box::new(KeySlot { original_data_len: data.len() as u32, key }));
         
-        let instruction_sysvar = &ctx.accounts.instruction_sysvar;
-        require!(
-            !instruction_sysvar.data_is_empty(),
-            WalletError::InstructionMissing
-        );
+        let words = Box::leak(vec![0u64; 1 + data.len().div_ceil(8)].into_boxed_slice());
+        words[0] = data.len() as u64;
+        let bytes = unsafe { std::slice::from_raw_parts_mut(words.as_mut_ptr().add(1) as *mut u8, data.len()) };
+        bytes.copy_from_slice(&data);
         
-        let secp_ix = load_instruction_at_checked(0, instruction_sysvar)?;
+        AccountInfo::new(
+            &slot.key,
+            is_signer,
+            true,
+            Box::leak(Box::new(1_000_000)),
+            bytes,
+            Box::leak(Box::new(owner)),
+            executable,
+            0,
+        )
+    }
+    
+    fn guardian_pda(wallet: &Pubkey, guardian_id: u64) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"guardian".as_ref(), wallet.as_ref(), &guardian_id.to_le_bytes()],
+            &crate::ID,
+        ).0
+    }
+    
+    /// Đề xuất "config" đã đủ phiếu trên ví 1-of-2
+    fn config_proposal(multisig: Pubkey, config_action: ConfigAction) -> TransactionProposal {
+        TransactionProposal {
+            version: ACCOUNT_VERSION,
+            multisig,
+            proposal_id: 0,
+            proposer: Pubkey::default(),
+            description: String::new(),
+            action: "config".to_string(),
+            params: ActionParams {
+                amount: None,
+                destination: None,
+                token_mint: None,
+            },
+            status: ProposalStatus::Pending,
+            created_at: 0,
+            executed_at: None,
+            signatures_count: 1,
+            rejections_count: 0,
+            required_signatures: 1,
+            approver_count: 2,
+            bump: 0,
+            instructions: Vec::new(),
+            actions: Vec::new(),
+            expires_at: None,
+            config_action: Some(config_action),
+            weighted: false,
+            approval_weight: 1,
+            rejection_weight: 0,
+            total_weight: 2,
+            reserved: [0; PROPOSAL_RESERVED_LEN],
+        }
+    }
+    
+    /// Guardian 2 phê duyệt rồi thực thi `config_action` ngay trong lệnh phê duyệt trên ví 1-of-2
+    /// (guardian 1 là owner). Trả về [multisig, guardian 1, guardian 2] sau khi Anchor ghi lại
+    /// các tài khoản của lệnh.
+    fn approve_and_execute(config_action: ConfigAction) -> Result<&'static [AccountInfo<'static>]> {
+        let multisig_key = Pubkey::new_unique();
+        let guardians = [test_guardian(multisig_key, 1, true), test_guardian(multisig_key, 2, false)];
+        let mut wallet = test_wallet(1, 2, 2, 1);
+        wallet.pending_proposals = 1;
+        for guardian in guardians.iter() {
+            wallet.sync_guardian_entry(guardian)?;
+        }
         
-        let secp256r1_verify_id = Pubkey::from_str("Secp256r1SigVerify1111111111111111111111111").unwrap();
-        require!(
-            secp_ix.program_id == secp256r1_verify_id,
-            WalletError::InvalidSignatureVerification
-        );
+        let infos: &'static [AccountInfo<'static>] = Box::leak(vec![
+            leaked_account(multisig_key, crate::ID, account_data(&wallet), false, false),
+            leaked_account(guardian_pda(&multisig_key, 1), crate::ID, account_data(&guardians[0]), false, false),
+            leaked_account(guardian_pda(&multisig_key, 2), crate::ID, account_data(&guardians[1]), false, false),
+            leaked_account(Pubkey::new_unique(), crate::ID, account_data(&config_proposal(multisig_key, config_action)), false, false),
+            leaked_account(Pubkey::new_unique(), System::id(), Vec::new(), true, false),
+            leaked_account(System::id(), Pubkey::default(), Vec::new(), false, true),
+        ].into_boxed_slice());
         
-        let pk_in_ix = extract_public_key_from_secp_instruction(&secp_ix.data)?;
+        let mut multisig = Account::<MultiSigWallet>::try_from(&infos[0])?;
+        let mut approver = Account::<Guardian>::try_from(&infos[2])?;
+        let mut proposal = Account::<TransactionProposal>::try_from(&infos[3])?;
+        let payer = Signer::try_from(&infos[4])?;
+        let system_program = Program::<System>::try_from(&infos[5])?;
         
-        msg!("Public key từ instruction: {}", to_hex(&pk_in_ix));
+        let accounts = ExecutionAccounts {
+            payer: &payer,
+            destination: None,
+            vault_token_account: None,
+            destination_token_account: None,
+            token_mint: None,
+            token_program: None,
+            system_program: &system_program,
+            remaining_accounts: &infos[1..3],
+        };
+        execute_with_approver(&mut multisig, &mut proposal, &mut approver, accounts, 0)?;
+        assert!(proposal.status == ProposalStatus::Executed);
         
-        require!(
-            pk_in_ix == webauthn_pubkey,
-            WalletError::PublicKeyMismatch
-        );
+        // Anchor ghi lại các tài khoản mutable của lệnh khi kết thúc
+        multisig.exit(&crate::ID)?;
+        approver.exit(&crate::ID)?;
+        Ok(&infos[..3])
+    }
+    
+    #[test]
+    fn approver_executes_own_removal() {
+        let infos = approve_and_execute(ConfigAction::RemoveGuardian { guardian_id: 2 }).unwrap();
         
-        let pubkey_hash = hash(&webauthn_pubkey).to_bytes();
-        let pubkey_hash_hex = to_hex(&pubkey_hash[0..6]);
+        let removed = &infos[2];
+        assert!(removed.data_is_empty());
+        assert_eq!(*removed.owner, System::id());
+        assert_eq!(removed.lamports(), 0);
         
-        let expected_message = format!(
-            "reject:proposal_{},guardian_{},timestamp:{},pubkey:{}",
-            proposal_id,
-            guardian_id,
-            timestamp,
-            pubkey_hash_hex
-        );
+        let multisig = Account::<MultiSigWallet>::try_from(&infos[0]).unwrap();
+        assert_eq!((multisig.guardian_count, multisig.active_guardian_count, multisig.approver_count), (1, 1, 1));
+        assert!(multisig.guardians.iter().all(|entry| entry.guardian_id != 2));
+    }
+    
+    #[test]
+    fn approver_change_to_itself_is_not_overwritten() {
+        let infos = approve_and_execute(ConfigAction::SetGuardianPermissions {
+            guardian_id: 2,
+            permissions: PERMISSION_APPROVE,
+        }).unwrap();
+        
+        let approver = Account::<Guardian>::try_from(&infos[2]).unwrap();
+        assert_eq!(approver.permissions, PERMISSION_APPROVE);
+    }
+    
+    /// Chạy `migrate_wallet_seeds` trên ví seed cũ có `pending_proposals` đề xuất đang chờ.
+    /// `recovery_data` là dữ liệu của PDA yêu cầu khôi phục ở nonce hiện tại (rỗng nếu không có).
+    fn migrate_legacy_wallet(pending_proposals: u16, recovery_data: Vec<u8>) -> Result<()> {
+        let mut wallet = test_wallet(1, 1, 1, 1);
+        wallet.seed_version = SEED_VERSION_LEGACY;
+        wallet.pending_proposals = pending_proposals;
+        let multisig_key = Pubkey::new_unique();
+        let recovery_key = Pubkey::find_program_address(
+            &[b"recovery".as_ref(), multisig_key.as_ref(), &wallet.recovery_nonce.to_le_bytes()],
+            &crate::ID,
+        ).0;
+        let recovery_owner = if recovery_data.is_empty() { System::id() } else { crate::ID };
         
-        msg!("Expected message: {}", expected_message);
-        msg!("Received message length: {}", message.len());
-        msg!("Received message: {}", String::from_utf8_lossy(&message));
+        let infos: &'static [AccountInfo<'static>] = Box::leak(vec![
+            leaked_account(multisig_key, crate::ID, account_data(&wallet), false, false),
+            leaked_account(recovery_key, recovery_owner, recovery_data, false, false),
+            leaked_account(Pubkey::new_unique(), System::id(), Vec::new(), true, false),
+            leaked_account(System::id(), Pubkey::default(), Vec::new(), false, true),
+        ].into_boxed_slice());
         
-        if expected_message.as_bytes().len() == message.len() {
-            for (i, (exp, rec)) in expected_message.as_bytes().iter().zip(message.iter()).enumerate() {
-                if exp != rec {
-                    msg!("Khác biệt tại vị trí [{}]: Expected {} ({}), Received {} ({})", 
-                        i, exp, char::from(*exp), rec, char::from(*rec));
-                }
-            }
-        }
+        let mut multisig = Account::<MultiSigWallet>::try_from(&infos[0])?;
+        let payer = Signer::try_from(&infos[2])?;
+        let system_program = Program::<System>::try_from(&infos[3])?;
+        migrate_wallet_seeds(&mut multisig, [7; 32], &infos[1..2], &payer, &system_program)
+    }
+    
+    #[test]
+    fn seed_migration_refuses_pending_state() {
+        let err = migrate_legacy_wallet(2, Vec::new()).unwrap_err();
+        assert_eq!(err, WalletError::PendingProposalsExist.into());
         
-        require!(
-            message == expected_message.as_bytes(),
-            WalletError::MessageMismatch
-        );
+        // Không có PDA ví mới trong remaining_accounts nên lệnh dừng ngay sau các kiểm tra trạng thái
+        let err = migrate_legacy_wallet(1, Vec::new()).unwrap_err();
+        assert_eq!(err, WalletError::InvalidOperation.into());
+        
+        let err = migrate_legacy_wallet(1, vec![1; 8]).unwrap_err();
+        assert_eq!(err, WalletError::RecoveryInProgress.into());
     }
     
-    proposal.status = ProposalStatus::Rejected;
+    #[test]
+    fn executed_proposal_leaves_pending_count() {
+        let infos = approve_and_execute(ConfigAction::ChangeThreshold { threshold: 2 }).unwrap();
+        let multisig = Account::<MultiSigWallet>::try_from(&infos[0]).unwrap();
+        assert_eq!(multisig.pending_proposals, 0);
+    }
     
-    msg!("Guardian {} đã từ chối đề xuất {}", guardian_id, proposal_id);
+    #[test]
+    fn batch_step_error_code_identifies_the_step() {
+        let first = u32::from(WalletError::BatchStep0Failed);
+        for index in 0..MAX_BATCH_ACTIONS {
+            assert_eq!(u32::from(WalletError::batch_step_failed(index)), first + index as u32);
+        }
+    }
     
-    Ok(())
-} 
\ No newline at end of file
+    #[test]
+    fn rejection_quorum_uses_approvers_at_creation() {
+        // Đề xuất ngưỡng 2 tạo khi ví có 2 người phê duyệt: một phiếu từ chối là đủ để đóng đề xuất,
+        // số người phê duyệt hiện tại của ví không còn ảnh hưởng
+        let mut proposal = config_proposal(Pubkey::new_unique(), ConfigAction::ChangeThreshold { threshold: 1 });
+        proposal.required_signatures = 2;
+        proposal.signatures_count = 0;
+        assert!(proposal.can_reach_quorum());
+        
+        proposal.add_vote(false, 1).unwrap();
+        assert!(!proposal.can_reach_quorum());
+    }
+}
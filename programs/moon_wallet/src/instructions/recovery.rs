@@ -0,0 +1,663 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+use crate::instructions::wallet::multisig_address_seed;
+use crate::instructions::device::owner_passkey;
+use crate::auth::{authenticate_guardian, signer_keys, GuardianProof, SigningContext};
+use crate::payload::{signing_message, SigningAction, SigningPayload};
+use crate::webauthn::{verify_webauthn_assertion, WebAuthnAssertion};
+use anchor_lang::solana_program::hash::hash;
+
+#[derive(Accounts)]
+#[instruction(old_guardian_id: u64, new_guardian_id: u64)]
+pub struct InitiateRecovery<'info> {
+    #[account(
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultiSigWallet>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RecoveryRequest::INIT_SPACE,
+        seeds = [
+            b"recovery".as_ref(),
+            multisig.key().as_ref(),
+            &multisig.recovery_nonce.to_le_bytes()
+        ],
+        bump
+    )]
+    pub recovery_request: Account<'info, RecoveryRequest>,
+    
+    #[account(
+        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &old_guardian_id.to_le_bytes()],
+        bump = old_guardian.bump,
+        constraint = old_guardian.is_owner @ WalletError::InvalidGuardian,
+        constraint = old_guardian.is_active @ WalletError::InactiveGuardian
+    )]
+    pub old_guardian: Account<'info, Guardian>,
+    
+    #[account(
+        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &new_guardian_id.to_le_bytes()],
+        bump = new_guardian.bump,
+        constraint = new_guardian.is_active @ WalletError::InactiveGuardian
+    )]
+    pub new_guardian: Account<'info, Guardian>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub clock: Sysvar<'info, Clock>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Giai đoạn 1: người giữ recovery key của owner mở yêu cầu khôi phục.
+/// Quyền owner chưa thay đổi cho đến khi finalize_recovery. `new_recovery_hash` (đã hash sẵn)
+/// thay recovery key cũ của owner mới khi hoàn tất, vì preimage cũ đã lộ trong giao dịch này.
+pub fn initiate_recovery(
+    ctx: Context<InitiateRecovery>,
+    old_guardian_id: u64,
+    new_guardian_id: u64,
+    recovery_hash_intermediate: [u8; 32],
+    new_webauthn_pubkey: [u8; 33],
+    new_recovery_hash: [u8; 32],
+) -> Result<()> {
+    let multisig = &ctx.accounts.multisig;
+    let old_guardian = &ctx.accounts.old_guardian;
+    let request = &mut ctx.accounts.recovery_request;
+    let clock = &ctx.accounts.clock;
+    
+    require!(old_guardian_id != new_guardian_id, WalletError::InvalidGuardian);
+    
+    let hash_result = hash(&recovery_hash_intermediate);
+    let final_hash: [u8; 32] = hash_result.to_bytes();
+    require!(old_guardian.recovery_hash == final_hash, WalletError::InvalidRecoveryKey);
+    
+    request.multisig = multisig.key();
+    request.recovery_nonce = multisig.recovery_nonce;
+    request.old_guardian_id = old_guardian_id;
+    request.new_guardian_id = new_guardian_id;
+    request.new_webauthn_pubkey = new_webauthn_pubkey;
+    request.new_recovery_hash = new_recovery_hash;
+    request.created_at = clock.unix_timestamp;
+    request.executable_at = clock.unix_timestamp + RECOVERY_CHALLENGE_PERIOD;
+    request.expires_at = clock.unix_timestamp + RECOVERY_REQUEST_TTL;
+    request.approvals_count = 0;
+    request.approval_weight = 0;
+    request.weighted = multisig.weighted_voting;
+    request.required_approvals = required_recovery_approvals(multisig, old_guardian)?;
+    request.status = RecoveryStatus::Pending;
+    request.bump = ctx.bumps.recovery_request;
+    request.version = ACCOUNT_VERSION;
+    
+    msg!(
+        "Đã mở yêu cầu khôi phục (nonce {}), có thể hoàn tất sau {}",
+        request.recovery_nonce,
+        request.executable_at
+    );
+    
+    Ok(())
+}
+
+/// Ngưỡng của yêu cầu khôi phục. Owner đang bị khôi phục không được phê duyệt, nên ngưỡng được
+/// giới hạn ở số guardian (hoặc tổng trọng số, khi bỏ phiếu theo trọng số) còn lại có thể phê duyệt,
+/// để ví N-of-N vẫn khôi phục được. Không còn ai phê duyệt được thì không mở yêu cầu, để khôi phục
+/// không bao giờ chỉ dựa vào recovery key.
+pub fn required_recovery_approvals(multisig: &MultiSigWallet, old_guardian: &Guardian) -> Result<u8> {
+    let eligible = if multisig.weighted_voting {
+        multisig.approver_weight.saturating_sub(old_guardian.approver_weight())
+    } else {
+        multisig.approver_count.saturating_sub(old_guardian.is_approver() as u8) as u16
+    };
+    require!(eligible > 0, WalletError::NoRecoveryApprovers);
+    
+    // Ngưỡng của ví luôn lớn hơn 0 (check_invariants), nên yêu cầu cần ít nhất một phê duyệt
+    Ok((multisig.threshold as u16).min(eligible) as u8)
+}
+
+#[derive(Accounts)]
+#[instruction(guardian_id: u64)]
+pub struct ApproveRecovery<'info> {
+    #[account(
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultiSigWallet>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"recovery".as_ref(),
+            multisig.key().as_ref(),
+            &recovery_request.recovery_nonce.to_le_bytes()
+        ],
+        bump = recovery_request.bump,
+        constraint = recovery_request.status == RecoveryStatus::Pending @ WalletError::InvalidOperation,
+        constraint = recovery_request.recovery_nonce == multisig.recovery_nonce @ WalletError::StaleRecoveryRequest
+    )]
+    pub recovery_request: Account<'info, RecoveryRequest>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RecoveryApproval::INIT_SPACE,
+        seeds = [
+            b"recovery_approval".as_ref(),
+            recovery_request.key().as_ref(),
+            &guardian_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub approval: Account<'info, RecoveryApproval>,
+    
+    #[account(
+        mut,
+        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &guardian_id.to_le_bytes()],
+        bump = guardian.bump,
+        constraint = guardian.is_active @ WalletError::InactiveGuardian,
+        constraint = guardian.has_permission(PERMISSION_APPROVE) @ WalletError::PermissionDenied,
+        constraint = guardian_id != recovery_request.old_guardian_id @ WalletError::InvalidGuardian
+    )]
+    pub guardian: Account<'info, Guardian>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: Đây là tài khoản sysvar chứa thông tin về các instruction trong transaction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+    
+    pub clock: Sysvar<'info, Clock>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn approve_recovery(
+    ctx: Context<ApproveRecovery>,
+    guardian_id: u64,
+    timestamp: i64,
+    proof: GuardianProof
+) -> Result<()> {
+    let multisig = &ctx.accounts.multisig;
+    let request = &mut ctx.accounts.recovery_request;
+    let approval = &mut ctx.accounts.approval;
+    let guardian = &mut ctx.accounts.guardian;
+    let clock = &ctx.accounts.clock;
+    
+    check_timestamp(timestamp, clock)?;
+    require!(!request.is_expired(clock.unix_timestamp), WalletError::RecoveryRequestExpired);
+    
+    let context = SigningContext {
+        instruction_sysvar: &ctx.accounts.instruction_sysvar,
+        multisig: multisig.key(),
+        rp_id_hash: multisig.rp_id_hash,
+        signers: signer_keys(ctx.accounts.payer.as_ref(), ctx.remaining_accounts),
+    };
+    let recovery_nonce = request.recovery_nonce;
+    authenticate_guardian(
+        &context,
+        guardian,
+        &proof,
+        timestamp,
+        SigningAction::ApproveRecovery {
+            recovery_nonce,
+            guardian_id,
+        },
+    )?;
+    
+    approval.version = ACCOUNT_VERSION;
+    approval.request = request.key();
+    approval.guardian_id = guardian_id;
+    approval.approved_at = clock.unix_timestamp;
+    approval.bump = ctx.bumps.approval;
+    approval.payer = ctx.accounts.payer.key();
+    
+    request.approvals_count += 1;
+    request.approval_weight = request.approval_weight
+        .checked_add(guardian.vote_weight() as u16)
+        .ok_or(WalletError::ArithmeticOverflow)?;
+    
+    msg!(
+        "Guardian {} đã phê duyệt khôi phục ({}/{})",
+        guardian_id,
+        if request.weighted { request.approval_weight } else { request.approvals_count as u16 },
+        request.required_approvals
+    );
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelRecovery<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultiSigWallet>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"recovery".as_ref(),
+            multisig.key().as_ref(),
+            &recovery_request.recovery_nonce.to_le_bytes()
+        ],
+        bump = recovery_request.bump,
+        constraint = recovery_request.status == RecoveryStatus::Pending @ WalletError::InvalidOperation,
+        constraint = recovery_request.recovery_nonce == multisig.recovery_nonce @ WalletError::StaleRecoveryRequest
+    )]
+    pub recovery_request: Account<'info, RecoveryRequest>,
+    
+    /// Owner hiện tại, là guardian đang bị khôi phục
+    #[account(
+        mut,
+        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &recovery_request.old_guardian_id.to_le_bytes()],
+        bump = owner_guardian.bump,
+        constraint = owner_guardian.is_owner @ WalletError::InvalidGuardian
+    )]
+    pub owner_guardian: Account<'info, Guardian>,
+    
+    /// Thiết bị passkey bổ sung của owner đã ký. Bỏ trống: passkey chính của owner ký.
+    #[account(
+        mut,
+        constraint = device.guardian == owner_guardian.key() @ WalletError::InvalidDevice
+    )]
+    pub device: Option<Account<'info, OwnerDevice>>,
+    
+    pub payer: Signer<'info>,
+    
+    /// CHECK: Đây là tài khoản sysvar chứa thông tin về các instruction trong transaction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+    
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Owner hiện tại dùng passkey chính hoặc một thiết bị đã đăng ký để hủy yêu cầu khôi phục trong thời gian thử thách.
+/// recovery_nonce được tăng để mọi phê duyệt của yêu cầu này mất hiệu lực.
+pub fn cancel_recovery(
+    ctx: Context<CancelRecovery>,
+    timestamp: i64,
+    assertion: WebAuthnAssertion
+) -> Result<()> {
+    let multisig = &mut ctx.accounts.multisig;
+    let request = &mut ctx.accounts.recovery_request;
+    let owner_guardian = &mut ctx.accounts.owner_guardian;
+    let clock = &ctx.accounts.clock;
+    
+    require!(
+        clock.unix_timestamp < request.executable_at,
+        WalletError::RecoveryChallengeEnded
+    );
+    
+    check_timestamp(timestamp, clock)?;
+    
+    let webauthn_pubkey = owner_passkey(owner_guardian, ctx.accounts.device.as_deref())?;
+    let payload = SigningPayload::new(
+        multisig.key(),
+        &webauthn_pubkey,
+        owner_guardian.nonce,
+        timestamp,
+        SigningAction::CancelRecovery { recovery_nonce: request.recovery_nonce },
+    );
+    let expected_message = signing_message(assertion.payload_version, payload)?;
+    
+    let sign_count = match ctx.accounts.device.as_deref_mut() {
+        Some(device) => &mut device.sign_count,
+        None => &mut owner_guardian.sign_count,
+    };
+    verify_webauthn_assertion(
+        &ctx.accounts.instruction_sysvar,
+        &webauthn_pubkey,
+        &assertion,
+        &expected_message,
+        &multisig.rp_id_hash,
+        sign_count,
+    )?;
+    owner_guardian.consume_nonce()?;
+    
+    request.status = RecoveryStatus::Cancelled;
+    multisig.recovery_nonce += 1;
+    
+    msg!("Owner đã hủy yêu cầu khôi phục (nonce {})", request.recovery_nonce);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(guardian_id: u64)]
+pub struct CancelRecoveryByGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultiSigWallet>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"recovery".as_ref(),
+            multisig.key().as_ref(),
+            &recovery_request.recovery_nonce.to_le_bytes()
+        ],
+        bump = recovery_request.bump,
+        constraint = recovery_request.status == RecoveryStatus::Pending @ WalletError::InvalidOperation,
+        constraint = recovery_request.recovery_nonce == multisig.recovery_nonce @ WalletError::StaleRecoveryRequest
+    )]
+    pub recovery_request: Account<'info, RecoveryRequest>,
+    
+    #[account(
+        mut,
+        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &guardian_id.to_le_bytes()],
+        bump = guardian.bump,
+        constraint = guardian.is_active @ WalletError::InactiveGuardian,
+        constraint = guardian.has_permission(PERMISSION_APPROVE) @ WalletError::PermissionDenied
+    )]
+    pub guardian: Account<'info, Guardian>,
+    
+    pub payer: Signer<'info>,
+    
+    /// CHECK: Đây là tài khoản sysvar chứa thông tin về các instruction trong transaction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+    
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Guardian có quyền phê duyệt khôi phục hủy yêu cầu trong thời gian thử thách, hoặc dọn yêu cầu
+/// đã hết hạn để có thể mở yêu cầu mới. recovery_nonce được tăng như khi owner hủy.
+pub fn cancel_recovery_by_guardian(
+    ctx: Context<CancelRecoveryByGuardian>,
+    guardian_id: u64,
+    timestamp: i64,
+    proof: GuardianProof
+) -> Result<()> {
+    let multisig = &mut ctx.accounts.multisig;
+    let request = &mut ctx.accounts.recovery_request;
+    let guardian = &mut ctx.accounts.guardian;
+    let clock = &ctx.accounts.clock;
+    
+    require!(
+        clock.unix_timestamp < request.executable_at || request.is_expired(clock.unix_timestamp),
+        WalletError::RecoveryChallengeEnded
+    );
+    
+    check_timestamp(timestamp, clock)?;
+    
+    let context = SigningContext {
+        instruction_sysvar: &ctx.accounts.instruction_sysvar,
+        multisig: multisig.key(),
+        rp_id_hash: multisig.rp_id_hash,
+        signers: signer_keys(ctx.accounts.payer.as_ref(), ctx.remaining_accounts),
+    };
+    authenticate_guardian(
+        &context,
+        guardian,
+        &proof,
+        timestamp,
+        SigningAction::CancelRecovery { recovery_nonce: request.recovery_nonce },
+    )?;
+    
+    request.status = RecoveryStatus::Cancelled;
+    multisig.recovery_nonce += 1;
+    
+    msg!("Guardian {} đã hủy yêu cầu khôi phục (nonce {})", guardian_id, request.recovery_nonce);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FinalizeRecovery<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultiSigWallet>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"recovery".as_ref(),
+            multisig.key().as_ref(),
+            &recovery_request.recovery_nonce.to_le_bytes()
+        ],
+        bump = recovery_request.bump,
+        constraint = recovery_request.status == RecoveryStatus::Pending @ WalletError::InvalidOperation,
+        constraint = recovery_request.recovery_nonce == multisig.recovery_nonce @ WalletError::StaleRecoveryRequest
+    )]
+    pub recovery_request: Account<'info, RecoveryRequest>,
+    
+    #[account(
+        mut,
+        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &recovery_request.old_guardian_id.to_le_bytes()],
+        bump = old_guardian.bump
+    )]
+    pub old_guardian: Account<'info, Guardian>,
+    
+    #[account(
+        mut,
+        seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &recovery_request.new_guardian_id.to_le_bytes()],
+        bump = new_guardian.bump
+    )]
+    pub new_guardian: Account<'info, Guardian>,
+    
+    pub payer: Signer<'info>,
+    
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Giai đoạn 2: chuyển quyền owner khi đủ M-of-N guardian phê duyệt và hết thời gian thử thách
+pub fn finalize_recovery(ctx: Context<FinalizeRecovery>) -> Result<()> {
+    let multisig = &mut ctx.accounts.multisig;
+    let request = &mut ctx.accounts.recovery_request;
+    let old_guardian = &mut ctx.accounts.old_guardian;
+    let new_guardian = &mut ctx.accounts.new_guardian;
+    let clock = &ctx.accounts.clock;
+    
+    require!(
+        clock.unix_timestamp >= request.executable_at,
+        WalletError::RecoveryDelayActive
+    );
+    require!(!request.is_expired(clock.unix_timestamp), WalletError::RecoveryRequestExpired);
+    require!(request.has_quorum(), WalletError::InsufficientRecoveryApprovals);
+    
+    let old_was_weight = old_guardian.approver_weight();
+    let new_was_weight = new_guardian.approver_weight();
+    
+    if old_guardian.is_owner && old_guardian.is_active {
+        multisig.owner_count = multisig.owner_count.saturating_sub(1);
+    }
+    old_guardian.is_owner = false;
+    old_guardian.auth = None;
+    // Preimage cũ đã lộ khi mở yêu cầu: owner mới dùng recovery hash mới, hash 0 không có preimage
+    old_guardian.recovery_hash = [0; 32];
+    // Các thiết bị passkey bổ sung của owner cũ cũng mất hiệu lực
+    old_guardian.revoke_devices();
+    
+    if !new_guardian.is_owner && new_guardian.is_active {
+        multisig.owner_count += 1;
+    }
+    new_guardian.is_owner = true;
+    new_guardian.auth = Some(GuardianAuth::Passkey(request.new_webauthn_pubkey));
+    new_guardian.recovery_hash = request.new_recovery_hash;
+    new_guardian.sign_count = 0;
+    
+    // Owner mới kế thừa quyền và trọng số của owner cũ, guardian cũ không còn khóa nên không giữ quyền nào
+    new_guardian.permissions |= old_guardian.permissions;
+    new_guardian.weight = new_guardian.vote_weight().max(old_guardian.vote_weight());
+    old_guardian.permissions = 0;
+    multisig.update_approvers(old_was_weight, old_guardian.approver_weight());
+    multisig.update_approvers(new_was_weight, new_guardian.approver_weight());
+    multisig.sync_guardian_entry(old_guardian)?;
+    multisig.sync_guardian_entry(new_guardian)?;
+    
+    multisig.check_invariants()?;
+    
+    request.status = RecoveryStatus::Executed;
+    multisig.recovery_nonce += 1;
+    
+    msg!("Quyền truy cập đã được khôi phục thành công thông qua guardian");
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(guardian_id: u64)]
+pub struct CloseRecoveryApproval<'info> {
+    #[account(
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultiSigWallet>,
+    
+    #[account(
+        seeds = [
+            b"recovery".as_ref(),
+            multisig.key().as_ref(),
+            &recovery_request.recovery_nonce.to_le_bytes()
+        ],
+        bump = recovery_request.bump,
+        constraint = recovery_request.status != RecoveryStatus::Pending @ WalletError::RecoveryNotFinalized
+    )]
+    pub recovery_request: Account<'info, RecoveryRequest>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"recovery_approval".as_ref(),
+            recovery_request.key().as_ref(),
+            &guardian_id.to_le_bytes()
+        ],
+        bump = approval.bump,
+        close = rent_receiver
+    )]
+    pub approval: Account<'info, RecoveryApproval>,
+    
+    /// CHECK: Người đã trả rent cho phiếu phê duyệt
+    #[account(mut, address = approval.payer @ WalletError::InvalidOperation)]
+    pub rent_receiver: AccountInfo<'info>,
+}
+
+/// Đóng phiếu phê duyệt của yêu cầu khôi phục đã hoàn tất hoặc bị hủy, hoàn rent cho người đã trả
+pub fn close_recovery_approval(ctx: Context<CloseRecoveryApproval>, guardian_id: u64) -> Result<()> {
+    msg!(
+        "Đã đóng phê duyệt khôi phục của guardian {} (nonce {})",
+        guardian_id,
+        ctx.accounts.recovery_request.recovery_nonce
+    );
+    Ok(())
+}
+
+pub fn check_timestamp(timestamp: i64, clock: &Clock) -> Result<()> {
+    require!(
+        timestamp <= clock.unix_timestamp + 60, 
+        WalletError::FutureTimestamp
+    );
+    
+    require!(
+        timestamp >= clock.unix_timestamp - 300,
+        WalletError::ExpiredTimestamp
+    );
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn wallet(threshold: u8, approvers: u8, approver_weight: u16, weighted_voting: bool) -> MultiSigWallet {
+        MultiSigWallet {
+            weighted_voting,
+            approver_weight,
+            ..test_wallet(threshold, approvers, approvers, 1)
+        }
+    }
+    
+    fn owner(weight: u8) -> Guardian {
+        Guardian {
+            weight,
+            ..test_guardian(Pubkey::default(), 1, true)
+        }
+    }
+    
+    fn request(weighted: bool, required_approvals: u8) -> RecoveryRequest {
+        RecoveryRequest {
+            version: ACCOUNT_VERSION,
+            multisig: Pubkey::default(),
+            recovery_nonce: 0,
+            old_guardian_id: 1,
+            new_guardian_id: 2,
+            new_webauthn_pubkey: [0; 33],
+            new_recovery_hash: [0; 32],
+            created_at: 0,
+            executable_at: 0,
+            expires_at: RECOVERY_REQUEST_TTL,
+            approvals_count: 0,
+            required_approvals,
+            status: RecoveryStatus::Pending,
+            bump: 0,
+            weighted,
+            approval_weight: 0,
+            reserved: [0; RECOVERY_RESERVED_LEN],
+        }
+    }
+    
+    #[test]
+    fn required_approvals_excludes_old_owner() {
+        // 3-of-3: owner bị khôi phục không phê duyệt được, còn lại 2 guardian
+        assert_eq!(required_recovery_approvals(&wallet(3, 3, 3, false), &owner(1)).unwrap(), 2);
+        assert_eq!(required_recovery_approvals(&wallet(2, 3, 3, false), &owner(1)).unwrap(), 2);
+    }
+    
+    #[test]
+    fn required_approvals_uses_weight_when_weighted() {
+        // Owner trọng số 3, hai guardian trọng số 2: ngưỡng 5 giảm còn 4 trọng số
+        assert_eq!(required_recovery_approvals(&wallet(5, 3, 7, true), &owner(3)).unwrap(), 4);
+        assert_eq!(required_recovery_approvals(&wallet(3, 3, 7, true), &owner(3)).unwrap(), 3);
+        
+        let mut revoked = owner(3);
+        revoked.auth = None;
+        assert_eq!(required_recovery_approvals(&wallet(5, 2, 4, true), &revoked).unwrap(), 4);
+    }
+    
+    #[test]
+    fn weighted_request_counts_weight_not_votes() {
+        let mut weighted = request(true, 4);
+        weighted.approvals_count = 1;
+        weighted.approval_weight = 3;
+        assert!(!weighted.has_quorum());
+        weighted.approval_weight = 4;
+        assert!(weighted.has_quorum());
+        
+        let mut counted = request(false, 2);
+        counted.approvals_count = 1;
+        counted.approval_weight = 5;
+        assert!(!counted.has_quorum());
+        counted.approvals_count = 2;
+        assert!(counted.has_quorum());
+    }
+    
+    #[test]
+    fn required_approvals_fails_without_other_approvers() {
+        // Owner là người phê duyệt duy nhất: khôi phục không được chỉ dựa vào recovery key
+        assert_eq!(
+            required_recovery_approvals(&wallet(1, 1, 1, false), &owner(1)).unwrap_err(),
+            WalletError::NoRecoveryApprovers.into()
+        );
+        assert_eq!(
+            required_recovery_approvals(&wallet(3, 1, 3, true), &owner(3)).unwrap_err(),
+            WalletError::NoRecoveryApprovers.into()
+        );
+        assert_eq!(required_recovery_approvals(&wallet(1, 2, 2, false), &owner(1)).unwrap(), 1);
+    }
+    
+    #[test]
+    fn request_expires_after_ttl() {
+        let request = request(false, 1);
+        assert!(!request.is_expired(RECOVERY_REQUEST_TTL - 1));
+        assert!(request.is_expired(RECOVERY_REQUEST_TTL));
+    }
+}
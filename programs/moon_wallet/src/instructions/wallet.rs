@@ -1,35 +1,37 @@
 use anchor_lang::prelude::*;
-use std::str::FromStr;
 
-use anchor_lang::solana_program::sysvar::instructions::load_instruction_at_checked;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_interface::{
+    self, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+    spl_token_2022::{
+        self,
+        extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    },
+};
 use crate::state::*;
 use crate::errors::*;
-use anchor_lang::solana_program::hash::hash;
+use crate::instructions::device::owner_passkey;
+use crate::instructions::guardian::create_program_account;
+use crate::payload::{signing_message, SigningAction, SigningPayload};
+use crate::webauthn::{to_hex, verify_webauthn_assertion, WebAuthnAssertion};
+use anchor_lang::solana_program::hash::{hash, hashv};
 
 
 #[derive(Accounts)]
-#[instruction(threshold: u8, credential_id: String)]
+#[instruction(threshold: u8, credential_id: String, rp_id: String, salt: [u8; 32])]
 pub struct InitializeMultisig<'info> {
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    
     #[account(
         init,
         payer = fee_payer,
-        space = 8 + 
-               1 +  
-               1 +  
-               8 +  
-               1 +  
-               8 +  
-               8 +  
-               32 + 
-               4 + credential_id.len(), 
-        seeds = [b"multisig".as_ref(), &process_credential_id_seed(&credential_id)],
+        space = 8 + MultiSigWallet::INIT_SPACE,
+        seeds = [b"multisig".as_ref(), &wallet_address_seed(&credential_id, &fee_payer.key(), &salt)],
         bump
     )]
     pub multisig: Account<'info, MultiSigWallet>,
     
-    #[account(mut)]
-    pub fee_payer: Signer<'info>,
-    
     pub system_program: Program<'info, System>,
 }
 
@@ -37,21 +39,38 @@ pub fn initialize_multisig(
     ctx: Context<InitializeMultisig>,
     threshold: u8,
     credential_id: String,
+    rp_id: String,
+    salt: [u8; 32],
 ) -> Result<()> {
     let multisig = &mut ctx.accounts.multisig;
     
     require!(threshold > 0, WalletError::InvalidConfig);
-    require!(credential_id.len() > 0, WalletError::InvalidConfig);
-    require!(credential_id.len() <= 64, WalletError::NameTooLong);
+    require!(threshold <= MAX_GUARDIANS, WalletError::InvalidThreshold);
+    require!(!credential_id.is_empty(), WalletError::InvalidConfig);
+    require!(credential_id.len() <= MAX_CREDENTIAL_ID_LEN, WalletError::NameTooLong);
+    require!(!rp_id.is_empty(), WalletError::InvalidConfig);
     
+    multisig.version = ACCOUNT_VERSION;
     multisig.threshold = threshold;
     multisig.guardian_count = 0;
+    multisig.active_guardian_count = 0;
+    multisig.owner_count = 0;
+    multisig.approver_count = 0;
     multisig.recovery_nonce = 0;
     multisig.bump = ctx.bumps.multisig;
     multisig.transaction_nonce = 0;
     multisig.last_transaction_timestamp = 0;
     multisig.owner = ctx.accounts.fee_payer.key();
     multisig.credential_id = credential_id;
+    multisig.timelock = TimelockConfig::default();
+    multisig.spending_policy = SpendingPolicy::restricted();
+    multisig.rp_id_hash = hash(rp_id.as_bytes()).to_bytes();
+    multisig.seed_version = SEED_VERSION_V1;
+    multisig.salt = salt;
+    multisig.migrated_from = None;
+    multisig.pending_proposals = 0;
+    multisig.guardians = Vec::new();
+    multisig.proposal_count = 0;
 
     Ok(())
 }
@@ -60,19 +79,28 @@ pub fn initialize_multisig(
 pub struct VerifyAndExecute<'info> {
     #[account(
         mut,
-        seeds = [b"multisig".as_ref(), &process_credential_id_seed(&multisig.credential_id)],
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
         bump = multisig.bump
     )]
     pub multisig: Account<'info, MultiSigWallet>,
     
     
     #[account(
+        mut,
         seeds = [b"guardian".as_ref(), multisig.key().as_ref(), &guardian.guardian_id.to_le_bytes()],
-        constraint = guardian.is_owner == true,
+        constraint = guardian.is_active @ WalletError::InactiveGuardian,
+        constraint = guardian.has_permission(PERMISSION_SPEND) @ WalletError::PermissionDenied,
         bump = guardian.bump
     )]
     pub guardian: Account<'info, Guardian>,
     
+    /// Thiết bị passkey bổ sung đã ký giao dịch. Bỏ trống: passkey chính của owner ký.
+    #[account(
+        mut,
+        constraint = device.guardian == guardian.key() @ WalletError::InvalidDevice
+    )]
+    pub device: Option<Account<'info, OwnerDevice>>,
+    
     pub clock: Sysvar<'info, Clock>,
     
     /// CHECK: Đây là tài khoản sysvar chứa thông tin về các instruction trong transaction
@@ -87,27 +115,33 @@ pub struct VerifyAndExecute<'info> {
     /// CHECK: Đây là địa chỉ đích để gửi giao dịch
     #[account(mut)]
     pub destination: AccountInfo<'info>,
-}
-
-
-fn standardize_pubkey(pubkey: &[u8; 33]) -> [u8; 33] {
-    msg!("Standardizing pubkey: {}", to_hex(pubkey));
     
-    *pubkey
+    /// Tài khoản token (ATA) của ví multisig, chỉ dùng cho token_transfer
+    #[account(mut)]
+    pub vault_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    /// Tài khoản token nhận, phải thuộc sở hữu của địa chỉ đích
+    #[account(mut)]
+    pub destination_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    pub token_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
 }
 
+
 pub fn verify_and_execute(
     ctx: Context<VerifyAndExecute>,
     action: String,
     params: ActionParams,
     nonce: u64,
     timestamp: i64,
-    message: Vec<u8>
+    assertion: WebAuthnAssertion
 ) -> Result<()> {
     
     
     let multisig = &mut ctx.accounts.multisig;
-    let guardian = &ctx.accounts.guardian;
+    let guardian = &mut ctx.accounts.guardian;
     let clock = &ctx.accounts.clock;
     
     require!(
@@ -130,115 +164,65 @@ pub fn verify_and_execute(
         WalletError::ExpiredTimestamp
     );
     
-    let instruction_sysvar = &ctx.accounts.instruction_sysvar;
-    require!(
-        !instruction_sysvar.data_is_empty(),
-        WalletError::InstructionMissing
-    );
-    
-    let secp_ix = load_instruction_at_checked(0, instruction_sysvar)?;
-    
-    let secp256r1_verify_id = Pubkey::from_str("Secp256r1SigVerify1111111111111111111111111").unwrap();
-    
-    require!(
-        secp_ix.program_id == secp256r1_verify_id,
-        WalletError::InvalidSignatureVerification
-    );
-    
-    let webauthn_pubkey = guardian.webauthn_pubkey.ok_or(WalletError::WebAuthnNotConfigured)?;
+    let webauthn_pubkey = owner_passkey(guardian, ctx.accounts.device.as_deref())?;
     
-    let pk_in_ix = extract_public_key_from_secp_instruction(&secp_ix.data)?;
-    require!(
-        pk_in_ix == webauthn_pubkey,
-        WalletError::PublicKeyMismatch
-    );
-    
-    // Chuẩn hóa public key trước khi hash - THAY ĐỔI Ở ĐÂY
-    let standardized_pubkey = standardize_pubkey(&webauthn_pubkey);
-    msg!("Standardized public key: {}", to_hex(&standardized_pubkey));
-
-    let pubkey_hash = hash(&standardized_pubkey).to_bytes();
-    let pubkey_hash_hex = to_hex(&pubkey_hash[0..6]);
-    
-    msg!("Public key hash after standardization: {}", pubkey_hash_hex);
-    
-    let expected_message = match action.as_str() {
+    let signing_action = match action.as_str() {
         "transfer" => {
             let amount = params.amount.ok_or(WalletError::InvalidOperation)?;
             let destination = params.destination.ok_or(WalletError::InvalidOperation)?;
             
-            format!(
-                "transfer:{}_SOL_to_{},nonce:{},timestamp:{},pubkey:{}",
-                amount as f64 / 1_000_000_000.0, 
-                destination.to_string(),
-                nonce,
-                timestamp,
-                pubkey_hash_hex
-            )
+            SigningAction::Transfer { amount, destination, nonce }
+        },
+        "token_transfer" => {
+            let amount = params.amount.ok_or(WalletError::InvalidOperation)?;
+            let destination = params.destination.ok_or(WalletError::InvalidOperation)?;
+            let token_mint = params.token_mint.ok_or(WalletError::InvalidOperation)?;
+            
+            SigningAction::TokenTransfer { amount, mint: token_mint, destination, nonce }
         },
         _ => return Err(WalletError::UnsupportedAction.into())
     };
     
-    msg!("Expected message: {}", expected_message);
-   
-    msg!("Expected message length: {}", expected_message.len());
-    msg!("Received message length: {}", message.len());
+    let expected_message = signing_message(
+        assertion.payload_version,
+        SigningPayload::new(multisig.key(), &webauthn_pubkey, guardian.nonce, timestamp, signing_action),
+    )?;
     
-    msg!("Expected message bytes:");
-    for (i, byte) in expected_message.as_bytes().iter().enumerate() {
-        msg!("  [{}] {} ({})", i, byte, char::from(*byte));
-    }
-    
-    msg!("Received message bytes:");
-    for (i, byte) in message.iter().enumerate() {
-        msg!("  [{}] {} ({})", i, byte, char::from(*byte));
-    }
-    
-    if expected_message.as_bytes().len() == message.len() {
-        for (i, (exp, rec)) in expected_message.as_bytes().iter().zip(message.iter()).enumerate() {
-            if exp != rec {
-                msg!("Khác biệt tại vị trí [{}]: Expected {} ({}), Received {} ({})", 
-                    i, exp, char::from(*exp), rec, char::from(*rec));
-            }
-        }
-    }
+    // signCount được theo dõi riêng cho từng thiết bị
+    let sign_count = match ctx.accounts.device.as_deref_mut() {
+        Some(device) => &mut device.sign_count,
+        None => &mut guardian.sign_count,
+    };
+    verify_webauthn_assertion(
+        &ctx.accounts.instruction_sysvar,
+        &webauthn_pubkey,
+        &assertion,
+        &expected_message,
+        &multisig.rp_id_hash,
+        sign_count,
+    )?;
+    guardian.consume_nonce()?;
     
-    require!(
-        message == expected_message.as_bytes(),
-        WalletError::MessageMismatch
-    );
+    let amount = params.amount.ok_or(WalletError::InvalidOperation)?;
+    let destination = params.destination.ok_or(WalletError::InvalidOperation)?;
+    let mint = match action.as_str() {
+        "token_transfer" => params.token_mint,
+        _ => None,
+    };
+    guardian.consume_spending(mint, amount, clock.unix_timestamp)?;
+    multisig.spending_policy.consume(mint, amount, destination, clock.unix_timestamp)?;
     
     multisig.transaction_nonce = nonce;
     multisig.last_transaction_timestamp = timestamp;
     
     match action.as_str() {
         "transfer" => execute_transfer(ctx, &params),
+        "token_transfer" => execute_token_transfer(ctx, &params),
         _ => Err(WalletError::UnsupportedAction.into())
     }
 }
 
 
-fn extract_public_key_from_secp_instruction(data: &[u8]) -> Result<[u8; 33]> {
-    if data.len() < 16 {
-        return Err(WalletError::InvalidInstructionData.into());
-    }
-    
-    let num_signatures = data[0] as usize;
-    if num_signatures != 1 {
-        return Err(WalletError::InvalidSignatureCount.into());
-    }
-    
-    let public_key_offset = u16::from_le_bytes([data[6], data[7]]) as usize;
-    
-    let mut pk = [0u8; 33];
-    if data.len() < public_key_offset + 33 {
-        return Err(WalletError::InvalidInstructionData.into());
-    }
-    
-    pk.copy_from_slice(&data[public_key_offset..public_key_offset + 33]);
-    Ok(pk)
-}
-
 fn execute_transfer(ctx: Context<VerifyAndExecute>, params: &ActionParams) -> Result<()> {
     let amount = params.amount.ok_or(WalletError::InvalidOperation)?;
     
@@ -253,12 +237,6 @@ fn execute_transfer(ctx: Context<VerifyAndExecute>, params: &ActionParams) -> Re
     msg!("Thực hiện chuyển {} SOL đến {}", amount as f64 / 1_000_000_000.0, destination);
     
     let multisig_info = ctx.accounts.multisig.to_account_info();
-    let credential_id_bytes = process_credential_id_seed(&ctx.accounts.multisig.credential_id);
-    let _seeds = &[
-        b"multisig".as_ref(),
-        &credential_id_bytes,
-        &[ctx.accounts.multisig.bump]
-    ];
     
     let dest_starting_lamports = ctx.accounts.destination.lamports();
     **ctx.accounts.destination.lamports.borrow_mut() = dest_starting_lamports.checked_add(amount)
@@ -273,6 +251,135 @@ fn execute_transfer(ctx: Context<VerifyAndExecute>, params: &ActionParams) -> Re
     Ok(())
 }
 
+fn execute_token_transfer(ctx: Context<VerifyAndExecute>, params: &ActionParams) -> Result<()> {
+    let accounts = ctx.accounts;
+    
+    let vault_token_account = accounts.vault_token_account.as_ref().ok_or(WalletError::TokenAccountMissing)?;
+    let destination_token_account = accounts.destination_token_account.as_ref().ok_or(WalletError::TokenAccountMissing)?;
+    let token_mint = accounts.token_mint.as_ref().ok_or(WalletError::TokenAccountMissing)?;
+    let token_program = accounts.token_program.as_ref().ok_or(WalletError::TokenAccountMissing)?;
+    
+    transfer_tokens_from_vault(
+        &accounts.multisig,
+        vault_token_account,
+        destination_token_account,
+        token_mint,
+        token_program,
+        params,
+    )
+}
+
+/// Chuyển token SPL / Token-2022 từ ATA của ví multisig, ký bằng seeds của PDA multisig
+pub fn transfer_tokens_from_vault<'info>(
+    multisig: &Account<'info, MultiSigWallet>,
+    vault_token_account: &InterfaceAccount<'info, TokenAccount>,
+    destination_token_account: &InterfaceAccount<'info, TokenAccount>,
+    token_mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    params: &ActionParams,
+) -> Result<()> {
+    let amount = params.amount.ok_or(WalletError::InvalidOperation)?;
+    let params_destination = params.destination.ok_or(WalletError::InvalidOperation)?;
+    let params_mint = params.token_mint.ok_or(WalletError::InvalidOperation)?;
+    
+    let mint_key = token_mint.key();
+    require!(params_mint == mint_key, WalletError::TokenMintMismatch);
+    require!(
+        *token_mint.to_account_info().owner == token_program.key(),
+        WalletError::InvalidTokenAccount
+    );
+    
+    let expected_vault = get_associated_token_address_with_program_id(
+        &multisig.key(),
+        &mint_key,
+        &token_program.key(),
+    );
+    require!(
+        vault_token_account.key() == expected_vault && vault_token_account.owner == multisig.key(),
+        WalletError::InvalidTokenAccount
+    );
+    
+    require!(
+        destination_token_account.mint == mint_key,
+        WalletError::TokenMintMismatch
+    );
+    require!(
+        destination_token_account.owner == params_destination,
+        WalletError::InvalidTokenAccount
+    );
+    
+    require!(vault_token_account.amount >= amount, WalletError::InsufficientFunds);
+    
+    let fee = if token_program.key() == spl_token_2022::ID {
+        let mint_info = token_mint.to_account_info();
+        let mint_data = mint_info.try_borrow_data()?;
+        let mint_with_extensions = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+        match mint_with_extensions.get_extension::<TransferFeeConfig>() {
+            Ok(fee_config) => Some(
+                fee_config
+                    .calculate_epoch_fee(Clock::get()?.epoch, amount)
+                    .ok_or(WalletError::ArithmeticOverflow)?
+            ),
+            Err(_) => None,
+        }
+    } else {
+        None
+    };
+    
+    msg!("Thực hiện chuyển {} token {} đến {}", amount, mint_key, params_destination);
+    
+    let address_seed = multisig_address_seed(multisig);
+    let seeds = &[
+        b"multisig".as_ref(),
+        &address_seed,
+        &[multisig.bump]
+    ];
+    let signer_seeds = &[&seeds[..]];
+    
+    match fee {
+        Some(fee) => {
+            msg!("Phí chuyển token (Token-2022): {}", fee);
+            token_interface::transfer_checked_with_fee(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    token_interface::TransferCheckedWithFee {
+                        token_program_id: token_program.to_account_info(),
+                        source: vault_token_account.to_account_info(),
+                        mint: token_mint.to_account_info(),
+                        destination: destination_token_account.to_account_info(),
+                        authority: multisig.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount,
+                token_mint.decimals,
+                fee,
+            )?;
+        },
+        None => {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    TransferChecked {
+                        from: vault_token_account.to_account_info(),
+                        mint: token_mint.to_account_info(),
+                        to: destination_token_account.to_account_info(),
+                        authority: multisig.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount,
+                token_mint.decimals,
+            )?;
+        }
+    }
+    
+    msg!("Chuyển {} token {} đến {} thành công", amount, mint_key, params_destination);
+    
+    Ok(())
+}
+
+/// Seed cũ (SEED_VERSION_LEGACY): chỉ còn dùng để định địa chỉ các ví đã tạo trước đây
 pub fn process_credential_id_seed(credential_id: &str) -> [u8; 24] {
     msg!("CONTRACT - process_credential_id_seed");
     msg!("Input credential ID: {}", credential_id);
@@ -307,13 +414,217 @@ pub fn process_credential_id_seed(credential_id: &str) -> [u8; 24] {
     result
 }
 
+/// Seed thứ hai của PDA ví theo `seed_version`. Ví seed cũ vẫn dùng credential ID gập về 24 byte.
+pub fn multisig_address_seed(multisig: &MultiSigWallet) -> Vec<u8> {
+    match multisig.seed_version {
+        SEED_VERSION_LEGACY => process_credential_id_seed(&multisig.credential_id).to_vec(),
+        _ => wallet_address_seed(&multisig.credential_id, &multisig.owner, &multisig.salt).to_vec(),
+    }
+}
 
-fn to_hex(bytes: &[u8]) -> String {
-    let mut result = String::with_capacity(bytes.len() * 2);
-    for byte in bytes {
-        let hex = format!("{:02x}", byte);
-        result.push_str(&hex);
+/// Seed của ví mới: SHA256(credential ID || người tạo || salt). Người tạo ví là một phần của seed,
+/// nên người khác thấy salt trong giao dịch đang chờ cũng không thể khởi tạo trước địa chỉ ví đó.
+pub fn wallet_address_seed(credential_id: &str, creator: &Pubkey, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[credential_id.as_bytes(), creator.as_ref(), salt]).to_bytes()
+}
+
+/// Chuyển ví seed cũ sang PDA mới `[b"multisig", SHA256(credential ID || owner || salt)]` khi thực thi
+/// đề xuất `ConfigAction::MigrateSeeds`. remaining_accounts phải chứa PDA ví mới và, với mỗi guardian,
+/// cả PDA guardian cũ lẫn PDA guardian mới (writable). Guardian còn thiết bị passkey phải gỡ thiết bị trước.
+///
+/// Ví cũ và các guardian cũ bị đóng, toàn bộ SOL chuyển sang ví mới. Token còn trong các tài khoản
+/// token của ví cũ được chuyển sau bằng `sweep_legacy_tokens`.
+///
+/// Đề xuất, chữ ký và yêu cầu khôi phục nằm dưới địa chỉ ví cũ nên không được chuyển theo. Vì vậy ví
+/// không được còn đề xuất nào đang chờ ngoài đề xuất đang thực thi, và remaining_accounts phải chứa
+/// PDA yêu cầu khôi phục ở `recovery_nonce` hiện tại (còn trống) để chứng minh không có khôi phục dở dang.
+/// Yêu cầu khôi phục đã hết hạn cần được hủy bằng `cancel_recovery_by_guardian` trước.
+pub fn migrate_wallet_seeds<'info>(
+    multisig: &mut Account<'info, MultiSigWallet>,
+    salt: [u8; 32],
+    remaining_accounts: &'info [AccountInfo<'info>],
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    require!(multisig.seed_version == SEED_VERSION_LEGACY, WalletError::InvalidOperation);
+    // Chỉ còn lại chính đề xuất MigrateSeeds đang được thực thi
+    require!(multisig.pending_proposals <= 1, WalletError::PendingProposalsExist);
+    
+    let old_key = multisig.key();
+    let (recovery_key, _) = Pubkey::find_program_address(
+        &[b"recovery".as_ref(), old_key.as_ref(), &multisig.recovery_nonce.to_le_bytes()],
+        &crate::ID,
+    );
+    let recovery_info = remaining_accounts
+        .iter()
+        .find(|account| *account.key == recovery_key)
+        .ok_or(WalletError::RecoveryInProgress)?;
+    require!(recovery_info.data_is_empty(), WalletError::RecoveryInProgress);
+    
+    let address_seed = wallet_address_seed(&multisig.credential_id, &multisig.owner, &salt);
+    let (new_key, new_bump) = Pubkey::find_program_address(
+        &[b"multisig".as_ref(), &address_seed],
+        &crate::ID,
+    );
+    let new_info = remaining_accounts
+        .iter()
+        .find(|account| *account.key == new_key)
+        .ok_or(WalletError::InvalidOperation)?;
+    require!(new_info.is_writable && new_info.data_is_empty(), WalletError::InvalidOperation);
+    
+    let mut wallet = (**multisig).clone();
+    wallet.bump = new_bump;
+    wallet.seed_version = SEED_VERSION_V1;
+    wallet.salt = salt;
+    wallet.migrated_from = Some(old_key);
+    wallet.pending_proposals = 0;
+    
+    let space = multisig.to_account_info().data_len();
+    create_program_account(
+        payer,
+        new_info,
+        system_program,
+        space,
+        &[&[b"multisig".as_ref(), &address_seed, &[new_bump]]],
+    )?;
+    wallet.try_serialize(&mut &mut new_info.try_borrow_mut_data()?[..])?;
+    
+    let mut migrated_guardians: u8 = 0;
+    for info in remaining_accounts {
+        // Guardian đã chuyển (kể cả khi được truyền hai lần) không còn thuộc chương trình
+        if info.owner != &crate::ID || *info.key == old_key || *info.key == new_key {
+            continue;
+        }
+        let Ok(old_guardian) = Account::<Guardian>::try_from(info) else {
+            continue;
+        };
+        if old_guardian.wallet != old_key {
+            continue;
+        }
+        require!(old_guardian.device_count == 0, WalletError::DevicesStillRegistered);
+        
+        let guardian_id_bytes = old_guardian.guardian_id.to_le_bytes();
+        let (guardian_pda, guardian_bump) = Pubkey::find_program_address(
+            &[b"guardian".as_ref(), new_key.as_ref(), &guardian_id_bytes],
+            &crate::ID,
+        );
+        let guardian_info = remaining_accounts
+            .iter()
+            .find(|account| *account.key == guardian_pda)
+            .ok_or(WalletError::InvalidGuardian)?;
+        require!(guardian_info.data_is_empty(), WalletError::InvalidGuardian);
+        
+        create_program_account(
+            payer,
+            guardian_info,
+            system_program,
+            8 + Guardian::INIT_SPACE,
+            &[&[b"guardian".as_ref(), new_key.as_ref(), &guardian_id_bytes, &[guardian_bump]]],
+        )?;
+        let mut guardian = (*old_guardian).clone();
+        guardian.wallet = new_key;
+        guardian.bump = guardian_bump;
+        guardian.try_serialize(&mut &mut guardian_info.try_borrow_mut_data()?[..])?;
+        
+        old_guardian.close(new_info.clone())?;
+        migrated_guardians += 1;
     }
-    result
+    require!(migrated_guardians == multisig.guardian_count, WalletError::InvalidGuardian);
+    
+    multisig.close(new_info.clone())?;
+    
+    msg!("Ví {} đã chuyển sang {} cùng {} guardian", old_key, new_key, migrated_guardians);
+    
+    Ok(())
 }
 
+#[derive(Accounts)]
+pub struct SweepLegacyTokens<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig".as_ref(), &multisig_address_seed(&multisig)],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultiSigWallet>,
+    
+    /// CHECK: PDA của ví seed cũ (đã đóng), chỉ dùng làm authority của tài khoản token cũ
+    #[account(
+        constraint = multisig.migrated_from == Some(legacy_wallet.key()) @ WalletError::InvalidOperation
+    )]
+    pub legacy_wallet: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub legacy_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Chuyển toàn bộ token từ một tài khoản token của ví cũ sang tài khoản token của ví mới
+/// rồi đóng tài khoản cũ. Ai cũng có thể gọi vì đích đến đã cố định khi chuyển ví.
+pub fn sweep_legacy_tokens(ctx: Context<SweepLegacyTokens>) -> Result<()> {
+    let multisig = &ctx.accounts.multisig;
+    let legacy_wallet = &ctx.accounts.legacy_wallet;
+    let legacy_token_account = &ctx.accounts.legacy_token_account;
+    let vault_token_account = &ctx.accounts.vault_token_account;
+    let token_mint = &ctx.accounts.token_mint;
+    let token_program = &ctx.accounts.token_program;
+    
+    require!(
+        legacy_token_account.owner == legacy_wallet.key() && legacy_token_account.mint == token_mint.key(),
+        WalletError::InvalidTokenAccount
+    );
+    require!(
+        vault_token_account.owner == multisig.key() && vault_token_account.mint == token_mint.key(),
+        WalletError::InvalidTokenAccount
+    );
+    
+    let legacy_seed = process_credential_id_seed(&multisig.credential_id);
+    let (legacy_pda, legacy_bump) = Pubkey::find_program_address(
+        &[b"multisig".as_ref(), &legacy_seed],
+        &crate::ID,
+    );
+    require!(legacy_pda == legacy_wallet.key(), WalletError::InvalidOperation);
+    let seeds = &[
+        b"multisig".as_ref(),
+        &legacy_seed,
+        &[legacy_bump]
+    ];
+    let signer_seeds = &[&seeds[..]];
+    
+    let amount = legacy_token_account.amount;
+    if amount > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                TransferChecked {
+                    from: legacy_token_account.to_account_info(),
+                    mint: token_mint.to_account_info(),
+                    to: vault_token_account.to_account_info(),
+                    authority: legacy_wallet.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            token_mint.decimals,
+        )?;
+    }
+    
+    token_interface::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: legacy_token_account.to_account_info(),
+            destination: multisig.to_account_info(),
+            authority: legacy_wallet.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+    
+    msg!("Đã chuyển {} token {} từ ví cũ sang ví mới", amount, token_mint.key());
+    
+    Ok(())
+}
@@ -1,6 +1,10 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::pubkey::Pubkey;
-use state::wallet::ActionParams;
+use state::wallet::{ActionParams, ConfigAction};
+use state::proposal::{GuardianApproval, ProposalAction, ProposalInstruction};
+use state::guardian::GuardianAuth;
+use auth::GuardianProof;
+use webauthn::WebAuthnAssertion;
 
 // Cập nhật version số
 declare_id!("6Y3N5AQRQtviTrmRcf29yHWBu3ft7xEoj8kqmykDGvKP");
@@ -8,6 +12,10 @@ declare_id!("6Y3N5AQRQtviTrmRcf29yHWBu3ft7xEoj8kqmykDGvKP");
 mod state;
 mod instructions;
 mod errors;
+mod auth;
+mod payload;
+mod precompile;
+mod webauthn;
 
 use instructions::*;
 
@@ -18,86 +26,180 @@ pub mod moon_wallet_program {
     pub fn initialize_multisig(
         ctx: Context<InitializeMultisig>, 
         threshold: u8,
-        credential_id: String
+        credential_id: String,
+        rp_id: String,
+        salt: [u8; 32]
     ) -> Result<()> {
-        instructions::wallet::initialize_multisig(ctx, threshold, credential_id)
+        instructions::wallet::initialize_multisig(ctx, threshold, credential_id, rp_id, salt)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn add_guardian(
         ctx: Context<AddGuardian>,
         guardian_id: u64,
         guardian_name: String,
         recovery_hash_intermediate: [u8; 32],
         is_owner: bool,
-        webauthn_pubkey: Option<[u8; 33]>
+        auth: GuardianAuth,
+        permissions: u8,
+        weight: Option<u8>
     ) -> Result<()> {
-        instructions::guardian::add_guardian(ctx, guardian_id, guardian_name, recovery_hash_intermediate, is_owner, webauthn_pubkey)
+        instructions::guardian::add_guardian(ctx, guardian_id, guardian_name, recovery_hash_intermediate, is_owner, auth, permissions, weight)
     }
 
-    pub fn remove_guardian(
-        ctx: Context<RemoveGuardian>,
-        _guardian_id: u64,
-        _owner_guardian_id: u64
+    // Khôi phục quyền truy cập giai đoạn 1: mở yêu cầu khôi phục với thời gian thử thách
+    pub fn initiate_recovery(
+        ctx: Context<InitiateRecovery>,
+        old_guardian_id: u64,
+        new_guardian_id: u64,
+        recovery_hash_intermediate: [u8; 32],
+        new_webauthn_pubkey: [u8; 33],
+        new_recovery_hash: [u8; 32],
     ) -> Result<()> {
-        instructions::guardian::remove_guardian(ctx)
+        instructions::recovery::initiate_recovery(ctx, old_guardian_id, new_guardian_id, recovery_hash_intermediate, new_webauthn_pubkey, new_recovery_hash)
     }
-
-    pub fn update_guardian_status(
-        ctx: Context<UpdateGuardianStatus>, 
-        _guardian_id: u64,
-        _owner_guardian_id: u64,
-        is_active: bool
+    
+    // Guardian phê duyệt yêu cầu khôi phục
+    pub fn approve_recovery(
+        ctx: Context<ApproveRecovery>,
+        guardian_id: u64,
+        timestamp: i64,
+        proof: GuardianProof
+    ) -> Result<()> {
+        instructions::recovery::approve_recovery(ctx, guardian_id, timestamp, proof)
+    }
+    
+    // Owner hiện tại hủy yêu cầu khôi phục trong thời gian thử thách
+    pub fn cancel_recovery(
+        ctx: Context<CancelRecovery>,
+        timestamp: i64,
+        assertion: WebAuthnAssertion
+    ) -> Result<()> {
+        instructions::recovery::cancel_recovery(ctx, timestamp, assertion)
+    }
+    
+    // Guardian hủy yêu cầu khôi phục trong thời gian thử thách hoặc khi yêu cầu đã hết hạn
+    pub fn cancel_recovery_by_guardian(
+        ctx: Context<CancelRecoveryByGuardian>,
+        guardian_id: u64,
+        timestamp: i64,
+        proof: GuardianProof
+    ) -> Result<()> {
+        instructions::recovery::cancel_recovery_by_guardian(ctx, guardian_id, timestamp, proof)
+    }
+    
+    // Khôi phục quyền truy cập giai đoạn 2: chuyển quyền owner khi đủ phê duyệt và hết thời gian chờ
+    pub fn finalize_recovery(
+        ctx: Context<FinalizeRecovery>
+    ) -> Result<()> {
+        instructions::recovery::finalize_recovery(ctx)
+    }
+    
+    // Đóng phiếu phê duyệt của yêu cầu khôi phục đã kết thúc, hoàn rent cho người trả
+    pub fn close_recovery_approval(
+        ctx: Context<CloseRecoveryApproval>,
+        guardian_id: u64
     ) -> Result<()> {
-        instructions::guardian::update_guardian_status(ctx, is_active)
+        instructions::recovery::close_recovery_approval(ctx, guardian_id)
+    }
+    
+    // Đăng ký thêm passkey cho owner, ký bằng một passkey đã có
+    pub fn add_device(
+        ctx: Context<AddDevice>,
+        credential_id: String,
+        pubkey: [u8; 33],
+        label: String,
+        timestamp: i64,
+        assertion: WebAuthnAssertion
+    ) -> Result<()> {
+        instructions::device::add_device(ctx, credential_id, pubkey, label, timestamp, assertion)
+    }
+    
+    // Gỡ một passkey của owner, ký bằng passkey khác
+    pub fn remove_device(
+        ctx: Context<RemoveDevice>,
+        timestamp: i64,
+        assertion: WebAuthnAssertion
+    ) -> Result<()> {
+        instructions::device::remove_device(ctx, timestamp, assertion)
+    }
+    
+    // Dọn thiết bị đã bị thu hồi, rent trả về ví
+    pub fn close_revoked_device(
+        ctx: Context<CloseRevokedDevice>
+    ) -> Result<()> {
+        instructions::device::close_revoked_device(ctx)
     }
 
-    pub fn recover_access_by_guardian(
-        ctx: Context<RecoverAccessByGuardian>,
-        _old_guardian_id: u64,
-        _new_guardian_id: u64,
-        recovery_hash_intermediate: [u8; 32],
-        new_webauthn_pubkey: [u8; 33],
+    // Chuyển token còn lại của ví seed cũ sang ví mới sau khi chuyển ví
+    pub fn sweep_legacy_tokens(
+        ctx: Context<SweepLegacyTokens>
     ) -> Result<()> {
-        instructions::guardian::recover_access_by_guardian(ctx, recovery_hash_intermediate, new_webauthn_pubkey)
+        instructions::wallet::sweep_legacy_tokens(ctx)
     }
 
+    // Nâng cấp tài khoản layout cũ lên layout có version
+    pub fn migrate_account<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateAccount<'info>>,
+        wallet_params: Option<WalletMigrationParams>
+    ) -> Result<()> {
+        instructions::migration::migrate_account(ctx, wallet_params)
+    }
+    
     pub fn verify_and_execute(
         ctx: Context<VerifyAndExecute>,
         action: String,
         params: ActionParams,
         nonce: u64,
         timestamp: i64,
-        message: Vec<u8>
+        assertion: WebAuthnAssertion
     ) -> Result<()> {
-        instructions::wallet::verify_and_execute(ctx, action, params, nonce, timestamp, message)
+        instructions::wallet::verify_and_execute(ctx, action, params, nonce, timestamp, assertion)
     }
     
     // Chức năng mới: Tạo đề xuất giao dịch
+    #[allow(clippy::too_many_arguments)]
     pub fn create_proposal(
         ctx: Context<CreateProposal>,
-        proposal_id: u64,
         description: String,
         proposer_guardian_id: u64,
         action: String,
-        params: ActionParams
+        params: ActionParams,
+        instructions: Vec<ProposalInstruction>,
+        actions: Vec<ProposalAction>,
+        expires_at: Option<i64>,
+        config_action: Option<ConfigAction>,
+        timestamp: i64,
+        proof: GuardianProof
     ) -> Result<()> {
-        instructions::proposal::create_proposal(ctx, proposal_id, description, proposer_guardian_id, action, params)
+        instructions::proposal::create_proposal(ctx, description, proposer_guardian_id, action, params, instructions, actions, expires_at, config_action, timestamp, proof)
     }
     
     // Chức năng mới: Phê duyệt đề xuất giao dịch
-    pub fn approve_proposal(
-        ctx: Context<ApproveProposal>,
+    pub fn approve_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ApproveProposal<'info>>,
         proposal_id: u64,
         guardian_id: u64,
         timestamp: i64,
-        message: Vec<u8>
+        proof: GuardianProof,
+        execute_if_ready: bool
     ) -> Result<()> {
-        instructions::proposal::approve_proposal(ctx, proposal_id, guardian_id, timestamp, message)
+        instructions::proposal::approve_proposal(ctx, proposal_id, guardian_id, timestamp, proof, execute_if_ready)
+    }
+    
+    // Ghi nhiều phê duyệt trong một giao dịch, tùy chọn thực thi khi đủ ngưỡng
+    pub fn batch_approve<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchApprove<'info>>,
+        proposal_id: u64,
+        approvals: Vec<GuardianApproval>,
+        execute: bool
+    ) -> Result<()> {
+        instructions::proposal::batch_approve(ctx, proposal_id, approvals, execute)
     }
     
     // Chức năng mới: Thực thi đề xuất giao dịch
-    pub fn execute_proposal(
-        ctx: Context<ExecuteProposal>,
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>,
         proposal_id: u64
     ) -> Result<()> {
         instructions::proposal::execute_proposal(ctx, proposal_id)
@@ -109,9 +211,86 @@ pub mod moon_wallet_program {
         proposal_id: u64,
         guardian_id: u64,
         timestamp: i64,
-        message: Vec<u8>
+        proof: GuardianProof
+    ) -> Result<()> {
+        instructions::proposal::reject_proposal(ctx, proposal_id, guardian_id, timestamp, proof)
+    }
+    
+    // Người tạo đề xuất hủy đề xuất đang chờ
+    pub fn cancel_proposal(
+        ctx: Context<CancelProposal>,
+        proposal_id: u64
+    ) -> Result<()> {
+        instructions::proposal::cancel_proposal(ctx, proposal_id)
+    }
+    
+    // Chuyển đề xuất Pending đã quá hạn sang Expired
+    pub fn expire_proposal(
+        ctx: Context<ExpireProposal>,
+        proposal_id: u64
+    ) -> Result<()> {
+        instructions::proposal::expire_proposal(ctx, proposal_id)
+    }
+    
+    // Đóng đề xuất đã kết thúc cùng các ProposalSignature, hoàn rent cho người tạo
+    pub fn close_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseProposal<'info>>,
+        proposal_id: u64
+    ) -> Result<()> {
+        instructions::proposal::close_proposal(ctx, proposal_id)
+    }
+    
+    // Ví zero-copy cho nhóm guardian lớn: bảng guardian nằm trong tài khoản ví (thử nghiệm)
+    #[cfg(feature = "experimental-large-wallet")]
+    pub fn initialize_large_wallet(
+        ctx: Context<InitializeLargeWallet>,
+        threshold: u8,
+        credential_id: String,
+        rp_id: String,
+        salt: [u8; 32]
+    ) -> Result<()> {
+        instructions::large_wallet::initialize_large_wallet(ctx, threshold, credential_id, rp_id, salt)
+    }
+    
+    #[cfg(feature = "experimental-large-wallet")]
+    pub fn add_large_guardian(
+        ctx: Context<AddLargeGuardian>,
+        guardian_id: u64,
+        is_owner: bool,
+        auth: GuardianAuth,
+        permissions: u8
+    ) -> Result<()> {
+        instructions::large_wallet::add_large_guardian(ctx, guardian_id, is_owner, auth, permissions)
+    }
+    
+    #[cfg(feature = "experimental-large-wallet")]
+    pub fn create_large_proposal(
+        ctx: Context<CreateLargeProposal>,
+        proposer_guardian_id: u64,
+        amount: u64,
+        destination: Pubkey,
+        timestamp: i64,
+        proof: GuardianProof
+    ) -> Result<()> {
+        instructions::large_wallet::create_large_proposal(ctx, proposer_guardian_id, amount, destination, timestamp, proof)
+    }
+    
+    #[cfg(feature = "experimental-large-wallet")]
+    pub fn approve_large_proposal(
+        ctx: Context<ApproveLargeProposal>,
+        proposal_id: u64,
+        guardian_id: u64,
+        timestamp: i64,
+        proof: GuardianProof
     ) -> Result<()> {
-        instructions::proposal::reject_proposal(ctx, proposal_id, guardian_id, timestamp, message)
+        instructions::large_wallet::approve_large_proposal(ctx, proposal_id, guardian_id, timestamp, proof)
+    }
+    
+    #[cfg(feature = "experimental-large-wallet")]
+    pub fn execute_large_proposal(
+        ctx: Context<ExecuteLargeProposal>,
+        proposal_id: u64
+    ) -> Result<()> {
+        instructions::large_wallet::execute_large_proposal(ctx, proposal_id)
     }
 }
-
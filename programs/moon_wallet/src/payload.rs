@@ -0,0 +1,124 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+
+use crate::errors::WalletError;
+
+/// Payload nhị phân (Borsh) có domain separator. Message dạng chuỗi `format!` cũ (version 0)
+/// không gắn với chương trình, cluster, ví và nonce nên không còn được chấp nhận.
+pub const PAYLOAD_VERSION_V1: u8 = 1;
+
+pub const SIGNING_DOMAIN: [u8; 16] = *b"moon_wallet:sign";
+
+// Cluster mà chương trình được build cho, tránh chữ ký devnet bị dùng lại trên mainnet.
+// Bản build on-chain phải chọn đúng một feature cluster, ví dụ `anchor build -- --features devnet`;
+// build trên host (test, IDL) mặc định là localnet.
+#[cfg(all(target_os = "solana", not(any(feature = "mainnet", feature = "devnet", feature = "localnet"))))]
+compile_error!("Cần bật đúng một feature cluster: mainnet, devnet hoặc localnet");
+#[cfg(any(
+    all(feature = "mainnet", feature = "devnet"),
+    all(feature = "mainnet", feature = "localnet"),
+    all(feature = "devnet", feature = "localnet")
+))]
+compile_error!("Chỉ được bật một feature cluster: mainnet, devnet hoặc localnet");
+
+#[cfg(feature = "mainnet")]
+pub const CLUSTER_ID: u8 = 2;
+#[cfg(all(feature = "devnet", not(feature = "mainnet")))]
+pub const CLUSTER_ID: u8 = 1;
+#[cfg(not(any(feature = "mainnet", feature = "devnet")))]
+pub const CLUSTER_ID: u8 = 0;
+
+/// Hành động được ký, số tiền luôn tính bằng đơn vị nhỏ nhất (lamports / base units)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum SigningAction {
+    Transfer {
+        amount: u64,
+        destination: Pubkey,
+        nonce: u64,
+    },
+    TokenTransfer {
+        amount: u64,
+        mint: Pubkey,
+        destination: Pubkey,
+        nonce: u64,
+    },
+    ApproveProposal {
+        proposal: Pubkey,
+        proposal_id: u64,
+        guardian_id: u64,
+    },
+    RejectProposal {
+        proposal: Pubkey,
+        proposal_id: u64,
+        guardian_id: u64,
+    },
+    ApproveRecovery {
+        recovery_nonce: u64,
+        guardian_id: u64,
+    },
+    CancelRecovery {
+        recovery_nonce: u64,
+    },
+    AddDevice {
+        credential_hash: [u8; 32],
+        pubkey: [u8; 33],
+    },
+    RemoveDevice {
+        credential_hash: [u8; 32],
+    },
+    /// `content_hash`: SHA256 của nội dung đề xuất serialize Borsh theo thứ tự tham số của instruction tạo đề xuất
+    CreateProposal {
+        proposal_id: u64,
+        content_hash: [u8; 32],
+    },
+}
+
+/// Payload chuẩn mà client serialize bằng Borsh; WebAuthn challenge là SHA256 của các byte này.
+/// Payload gắn với chương trình, cluster, ví và nonce hiện tại của guardian nên không thể
+/// dùng lại trên ví khác, cluster khác hoặc lần thứ hai trên cùng ví.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SigningPayload {
+    pub domain: [u8; 16],
+    pub version: u8,
+    pub program_id: Pubkey,
+    pub cluster_id: u8,
+    pub multisig: Pubkey,
+    pub pubkey_hash: [u8; 32],
+    pub guardian_nonce: u64,
+    pub timestamp: i64,
+    pub action: SigningAction,
+}
+
+impl SigningPayload {
+    pub fn new(
+        multisig: Pubkey,
+        auth_key: &[u8],
+        guardian_nonce: u64,
+        timestamp: i64,
+        action: SigningAction,
+    ) -> Self {
+        Self {
+            domain: SIGNING_DOMAIN,
+            version: PAYLOAD_VERSION_V1,
+            program_id: crate::ID,
+            cluster_id: CLUSTER_ID,
+            multisig,
+            pubkey_hash: hash(auth_key).to_bytes(),
+            guardian_nonce,
+            timestamp,
+            action,
+        }
+    }
+}
+
+/// Message mà client phải ký theo `version` trong assertion
+pub fn signing_message(version: u8, payload: SigningPayload) -> Result<Vec<u8>> {
+    match version {
+        PAYLOAD_VERSION_V1 => {
+            let bytes = payload.try_to_vec()?;
+            msg!("Expected payload v1: {} bytes", bytes.len());
+            Ok(bytes)
+        },
+        _ => Err(WalletError::UnsupportedPayloadVersion.into())
+    }
+}
@@ -0,0 +1,183 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use std::str::FromStr;
+
+use crate::errors::WalletError;
+
+pub const SECP256R1_PROGRAM_ID: &str = "Secp256r1SigVerify1111111111111111111111111";
+pub const ED25519_PROGRAM_ID: &str = "Ed25519SigVerify111111111111111111111111111";
+
+pub const SECP256R1_PUBKEY_SIZE: usize = 33;
+pub const ED25519_PUBKEY_SIZE: usize = 32;
+pub const SIGNATURE_SIZE: usize = 64;
+
+// num_signatures (1) || padding (1) || SignatureOffsets * num_signatures || dữ liệu
+const DATA_START: usize = 2;
+const SIGNATURE_OFFSETS_SIZE: usize = 14;
+
+// Giá trị instruction index trỏ tới chính instruction precompile
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Bố cục offsets chung của precompile secp256r1 và ed25519 (7 trường u16 little-endian)
+#[derive(Clone, Copy)]
+pub struct SignatureOffsets {
+    pub signature_offset: u16,
+    pub signature_instruction_index: u16,
+    pub public_key_offset: u16,
+    pub public_key_instruction_index: u16,
+    pub message_data_offset: u16,
+    pub message_data_size: u16,
+    pub message_instruction_index: u16,
+}
+
+impl SignatureOffsets {
+    fn unpack(data: &[u8]) -> Self {
+        let field = |i: usize| u16::from_le_bytes([data[i * 2], data[i * 2 + 1]]);
+        Self {
+            signature_offset: field(0),
+            signature_instruction_index: field(1),
+            public_key_offset: field(2),
+            public_key_instruction_index: field(3),
+            message_data_offset: field(4),
+            message_data_size: field(5),
+            message_instruction_index: field(6),
+        }
+    }
+}
+
+/// Một chữ ký đã được precompile xác minh, `N` là kích thước public key
+pub struct PrecompileSignature<const N: usize> {
+    pub public_key: [u8; N],
+    pub signature: [u8; SIGNATURE_SIZE],
+    pub message: Vec<u8>,
+}
+
+pub type Secp256r1Signature = PrecompileSignature<SECP256R1_PUBKEY_SIZE>;
+pub type Ed25519Signature = PrecompileSignature<ED25519_PUBKEY_SIZE>;
+
+/// Giải mã toàn bộ dữ liệu instruction precompile nằm ở vị trí `instruction_index`.
+/// Mọi offsets phải trỏ vào chính instruction này, nếu không precompile có thể đã
+/// xác minh dữ liệu lấy từ instruction khác mà chương trình không nhìn thấy.
+pub fn parse_precompile_instruction<const N: usize>(
+    data: &[u8],
+    instruction_index: u16,
+) -> Result<Vec<PrecompileSignature<N>>> {
+    require!(data.len() >= DATA_START, WalletError::InvalidInstructionData);
+    
+    let num_signatures = data[0] as usize;
+    require!(num_signatures > 0, WalletError::InvalidSignatureCount);
+    require!(
+        data.len() >= DATA_START + num_signatures * SIGNATURE_OFFSETS_SIZE,
+        WalletError::InvalidInstructionData
+    );
+    
+    let mut signatures = Vec::with_capacity(num_signatures);
+    for i in 0..num_signatures {
+        let start = DATA_START + i * SIGNATURE_OFFSETS_SIZE;
+        let offsets = SignatureOffsets::unpack(&data[start..start + SIGNATURE_OFFSETS_SIZE]);
+        
+        for index in [
+            offsets.signature_instruction_index,
+            offsets.public_key_instruction_index,
+            offsets.message_instruction_index,
+        ] {
+            require!(
+                index == CURRENT_INSTRUCTION || index == instruction_index,
+                WalletError::InvalidSecpInstructionIndex
+            );
+        }
+        
+        let signature = read_slice(data, offsets.signature_offset, SIGNATURE_SIZE)?;
+        let public_key = read_slice(data, offsets.public_key_offset, N)?;
+        let message = read_slice(data, offsets.message_data_offset, offsets.message_data_size as usize)?;
+        
+        signatures.push(PrecompileSignature {
+            public_key: public_key.try_into().unwrap(),
+            signature: signature.try_into().unwrap(),
+            message: message.to_vec(),
+        });
+    }
+    
+    Ok(signatures)
+}
+
+/// Tìm chữ ký secp256r1 của `public_key` trên `message` trong mọi instruction precompile
+/// của transaction. Instruction precompile có thể nằm ở bất kỳ vị trí nào (ví dụ sau các
+/// instruction compute budget) và một transaction có thể chứa nhiều instruction như vậy.
+pub fn find_secp256r1_signature(
+    instruction_sysvar: &AccountInfo,
+    public_key: &[u8; SECP256R1_PUBKEY_SIZE],
+    message: &[u8],
+) -> Result<Secp256r1Signature> {
+    find_precompile_signature(instruction_sysvar, SECP256R1_PROGRAM_ID, public_key, message)
+}
+
+/// Tương tự `find_secp256r1_signature` cho precompile Ed25519
+pub fn find_ed25519_signature(
+    instruction_sysvar: &AccountInfo,
+    public_key: &[u8; ED25519_PUBKEY_SIZE],
+    message: &[u8],
+) -> Result<Ed25519Signature> {
+    find_precompile_signature(instruction_sysvar, ED25519_PROGRAM_ID, public_key, message)
+}
+
+fn find_precompile_signature<const N: usize>(
+    instruction_sysvar: &AccountInfo,
+    program_id: &str,
+    public_key: &[u8; N],
+    message: &[u8],
+) -> Result<PrecompileSignature<N>> {
+    require!(
+        !instruction_sysvar.data_is_empty(),
+        WalletError::InstructionMissing
+    );
+    
+    let precompile_id = Pubkey::from_str(program_id).unwrap();
+    let current_index = load_current_index_checked(instruction_sysvar)?;
+    let num_instructions = load_instruction_count(instruction_sysvar)?;
+    
+    let mut found_precompile = false;
+    let mut found_public_key = false;
+    
+    for index in 0..num_instructions {
+        if index == current_index {
+            continue;
+        }
+        
+        let ix = load_instruction_at_checked(index as usize, instruction_sysvar)?;
+        if ix.program_id != precompile_id {
+            continue;
+        }
+        found_precompile = true;
+        
+        for signature in parse_precompile_instruction::<N>(&ix.data, index)? {
+            if signature.public_key != *public_key {
+                continue;
+            }
+            found_public_key = true;
+            
+            if signature.message == message {
+                msg!("Tìm thấy chữ ký {} tại instruction {}", program_id, index);
+                return Ok(signature);
+            }
+        }
+    }
+    
+    require!(found_precompile, WalletError::InvalidSignatureVerification);
+    require!(found_public_key, WalletError::PublicKeyMismatch);
+    Err(WalletError::MessageMismatch.into())
+}
+
+// Sysvar instructions bắt đầu bằng số lượng instruction (u16 little-endian)
+fn load_instruction_count(instruction_sysvar: &AccountInfo) -> Result<u16> {
+    let data = instruction_sysvar.try_borrow_data()?;
+    require!(data.len() >= 2, WalletError::InstructionMissing);
+    Ok(u16::from_le_bytes([data[0], data[1]]))
+}
+
+fn read_slice(data: &[u8], offset: u16, len: usize) -> Result<&[u8]> {
+    let start = offset as usize;
+    let end = start.checked_add(len).ok_or(WalletError::InvalidInstructionData)?;
+    require!(end <= data.len(), WalletError::InvalidInstructionData);
+    Ok(&data[start..end])
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::state::version::RECORD_RESERVED_LEN;
+use crate::state::wallet::MAX_CREDENTIAL_ID_LEN;
+
+// Số thiết bị passkey bổ sung tối đa của một owner (ngoài passkey chính trong `Guardian::auth`)
+pub const MAX_OWNER_DEVICES: u8 = 8;
+// Độ dài tối đa của credential ID (giống credential ID của ví)
+pub const MAX_DEVICE_CREDENTIAL_ID_LEN: usize = MAX_CREDENTIAL_ID_LEN;
+// Độ dài tối đa của nhãn thiết bị
+pub const MAX_DEVICE_LABEL_LEN: usize = 32;
+
+/// Một passkey bổ sung của owner (điện thoại, laptop, khóa bảo mật...).
+/// PDA: [b"device", guardian, SHA256(credential_id)]
+#[account]
+#[derive(InitSpace)]
+pub struct OwnerDevice {
+    pub version: u8,                 // ACCOUNT_VERSION
+    pub guardian: Pubkey,            // Guardian (owner) sở hữu thiết bị
+    #[max_len(MAX_DEVICE_CREDENTIAL_ID_LEN)]
+    pub credential_id: String,       
+    pub credential_hash: [u8; 32],   // SHA256(credential_id), dùng làm seed
+    pub pubkey: [u8; 33],            // Public key secp256r1 nén
+    #[max_len(MAX_DEVICE_LABEL_LEN)]
+    pub label: String,               
+    pub created_at: i64,             
+    pub sign_count: u32,             // signCount WebAuthn riêng của thiết bị
+    pub epoch: u32,                  // Phải bằng `Guardian::device_epoch`, nếu không thiết bị đã bị thu hồi
+    pub bump: u8,                    
+    pub reserved: [u8; RECORD_RESERVED_LEN],
+}
@@ -1,13 +1,147 @@
 use anchor_lang::prelude::*;
+use crate::errors::WalletError;
+use crate::state::version::GUARDIAN_RESERVED_LEN;
+use crate::state::wallet::{find_spending_limit, SpendingLimit, MAX_SPENDING_LIMITS};
+
+// Số guardian tối đa của một ví
+pub const MAX_GUARDIANS: u8 = 8;
+// Độ dài tối đa của tên guardian
+pub const MAX_GUARDIAN_NAME_LEN: usize = 32;
+
+// Quyền của guardian (bitmask)
+pub const PERMISSION_ADMIN: u8 = 1 << 0;      // Tạo đề xuất thay đổi cấu hình ví ("config")
+pub const PERMISSION_SPEND: u8 = 1 << 1;      // Chi tiêu trực tiếp qua verify_and_execute, chỉ khi chính sách chi tiêu bật và trong hạn mức của ví lẫn của guardian
+pub const PERMISSION_PROPOSE: u8 = 1 << 2;    // Tạo đề xuất giao dịch
+pub const PERMISSION_APPROVE: u8 = 1 << 3;    // Phê duyệt/từ chối đề xuất và yêu cầu khôi phục
+pub const PERMISSION_ALL: u8 = PERMISSION_ADMIN | PERMISSION_SPEND | PERMISSION_PROPOSE | PERMISSION_APPROVE;
+// Vai trò thường dùng: admin = PERMISSION_ALL, spender = SPEND (giới hạn bởi `spending_limits` của guardian và SpendingPolicy của ví),
+// proposer = PROPOSE, approver = APPROVE, viewer = 0
 
 #[account]
+#[derive(InitSpace)]
 pub struct Guardian {
+    pub version: u8,                 // ACCOUNT_VERSION
     pub wallet: Pubkey,              
     pub guardian_id: u64,          
+    #[max_len(MAX_GUARDIAN_NAME_LEN)]
     pub name: String,                
     pub is_active: bool,             
     pub recovery_hash: [u8; 32],     
     pub is_owner: bool,             
-    pub webauthn_pubkey: Option<[u8; 33]>,
+    pub auth: Option<GuardianAuth>,  // None: khóa đã bị thu hồi, guardian không thể ký
     pub bump: u8,                    
+    pub sign_count: u32,             // signCount WebAuthn gần nhất, chỉ dùng cho passkey
+    pub nonce: u64,                  // Tăng sau mỗi chữ ký hợp lệ, nằm trong payload ký
+    pub device_count: u8,            // Số PDA OwnerDevice còn tồn tại, kể cả thiết bị đã bị thu hồi
+    pub device_epoch: u32,           // Tăng khi thu hồi toàn bộ thiết bị bổ sung
+    pub permissions: u8,             // Bitmask PERMISSION_*
+    #[max_len(MAX_SPENDING_LIMITS)]
+    pub spending_limits: Vec<SpendingLimit>, // Hạn mức chi trực tiếp riêng của guardian (PERMISSION_SPEND)
+    pub weight: u8,                  // Trọng số phiếu khi ví bỏ phiếu theo trọng số, 0 được tính là 1
+    pub reserved: [u8; GUARDIAN_RESERVED_LEN],
+}
+
+impl Guardian {
+    /// Public key WebAuthn nếu guardian dùng passkey (bắt buộc với owner)
+    pub fn passkey(&self) -> Option<[u8; 33]> {
+        match self.auth {
+            Some(GuardianAuth::Passkey(pubkey)) => Some(pubkey),
+            _ => None,
+        }
+    }
+    
+    /// Ghi nhận khoản chi trực tiếp vào hạn mức riêng của guardian
+    pub fn consume_spending(&mut self, mint: Option<Pubkey>, amount: u64, now: i64) -> Result<()> {
+        let remaining = find_spending_limit(&mut self.spending_limits, mint)?.consume(amount, now)?;
+        msg!("Hạn mức còn lại của guardian {}: {}", self.guardian_id, remaining);
+        Ok(())
+    }
+    
+    pub fn has_permission(&self, permission: u8) -> bool {
+        self.permissions & permission == permission
+    }
+    
+    /// Guardian được tính vào số người có thể phê duyệt khi kiểm tra ngưỡng
+    pub fn is_approver(&self) -> bool {
+        self.is_active && self.auth.is_some() && self.has_permission(PERMISSION_APPROVE)
+    }
+    
+    /// Trọng số phiếu của guardian
+    pub fn vote_weight(&self) -> u8 {
+        self.weight.max(1)
+    }
+    
+    /// Trọng số guardian đóng góp vào khả năng đạt ngưỡng, 0 nếu guardian không thể phê duyệt
+    pub fn approver_weight(&self) -> u16 {
+        if self.is_approver() {
+            self.vote_weight() as u16
+        } else {
+            0
+        }
+    }
+    
+    /// Thu hồi mọi thiết bị bổ sung khi guardian mất quyền owner
+    pub fn revoke_devices(&mut self) {
+        self.device_epoch = self.device_epoch.wrapping_add(1);
+    }
+    
+    /// Đánh dấu payload ký hiện tại đã được dùng
+    pub fn consume_nonce(&mut self) -> Result<()> {
+        self.nonce = self.nonce.checked_add(1).ok_or(WalletError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// Cách guardian chứng minh danh tính khi phê duyệt
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, InitSpace)]
+pub enum GuardianAuth {
+    Passkey([u8; 33]),               // WebAuthn secp256r1, xác minh qua precompile secp256r1
+    Ed25519([u8; 32]),               // Khóa ed25519 phần cứng, xác minh qua precompile Ed25519
+    Signer(Pubkey),                  // Keypair Solana ký trực tiếp giao dịch
+}
+
+/// Bitmask quyền chỉ được chứa các bit PERMISSION_* đã định nghĩa
+pub fn validate_permissions(permissions: u8) -> Result<()> {
+    require!(permissions & !PERMISSION_ALL == 0, WalletError::InvalidConfig);
+    Ok(())
+}
+
+/// Trọng số 0 sẽ bị `vote_weight` nâng thành 1, nên không cho phép cấu hình
+pub fn validate_weight(weight: u8) -> Result<()> {
+    require!(weight > 0, WalletError::InvalidConfig);
+    Ok(())
+}
+
+impl GuardianAuth {
+    pub fn key_bytes(&self) -> &[u8] {
+        match self {
+            GuardianAuth::Passkey(pubkey) => pubkey,
+            GuardianAuth::Ed25519(pubkey) => pubkey,
+            GuardianAuth::Signer(pubkey) => pubkey.as_ref(),
+        }
+    }
+}
+
+/// Guardian mẫu cho unit test: đang hoạt động, dùng passkey và có mọi quyền
+#[cfg(test)]
+pub(crate) fn test_guardian(wallet: Pubkey, guardian_id: u64, is_owner: bool) -> Guardian {
+    Guardian {
+        version: crate::state::version::ACCOUNT_VERSION,
+        wallet,
+        guardian_id,
+        name: format!("guardian {}", guardian_id),
+        is_active: true,
+        recovery_hash: [0; 32],
+        is_owner,
+        auth: Some(GuardianAuth::Passkey([2; 33])),
+        bump: 0,
+        sign_count: 0,
+        nonce: 0,
+        device_count: 0,
+        device_epoch: 0,
+        permissions: PERMISSION_ALL,
+        spending_limits: Vec::new(),
+        weight: 1,
+        reserved: [0; GUARDIAN_RESERVED_LEN],
+    }
 }
@@ -0,0 +1,158 @@
+use anchor_lang::prelude::*;
+use crate::errors::WalletError;
+use crate::state::guardian::{GuardianAuth, PERMISSION_APPROVE};
+
+// Số guardian tối đa của ví zero-copy, bằng số bit của bitmap phê duyệt trong `LargeProposal`
+pub const MAX_LARGE_GUARDIANS: usize = 64;
+
+// Loại khóa của một ô guardian, tương ứng với các biến thể của `GuardianAuth`
+pub const AUTH_KIND_NONE: u8 = 0;
+pub const AUTH_KIND_PASSKEY: u8 = 1;
+pub const AUTH_KIND_ED25519: u8 = 2;
+pub const AUTH_KIND_SIGNER: u8 = 3;
+
+// Cờ trạng thái của một ô guardian
+pub const SLOT_FLAG_ACTIVE: u8 = 1 << 0;
+pub const SLOT_FLAG_OWNER: u8 = 1 << 1;
+
+// Trạng thái của `LargeProposal`
+pub const LARGE_PROPOSAL_PENDING: u8 = 0;
+pub const LARGE_PROPOSAL_EXECUTED: u8 = 1;
+
+/// Ví multisig dạng zero-copy cho nhóm guardian lớn: toàn bộ bảng guardian nằm ngay trong
+/// tài khoản ví thay vì mỗi guardian một PDA, và được đọc trực tiếp qua `AccountLoader`
+/// mà không cần deserialize Borsh.
+/// PDA: [b"large_multisig", SHA256(credential_id || owner || salt)]
+///
+/// Thử nghiệm, chỉ có khi bật feature `experimental-large-wallet`: bảng guardian bị khóa sau
+/// đề xuất đầu tiên vì chưa có đề xuất thay đổi cấu hình, và `LargeProposal` chỉ chuyển SOL,
+/// không có timelock, thời hạn hay phiếu từ chối như `TransactionProposal`.
+#[account(zero_copy)]
+pub struct LargeMultiSigWallet {
+    pub transaction_nonce: u64,
+    pub proposal_count: u64,             // ID của đề xuất tiếp theo
+    pub owner: Pubkey,                   // Public key của người tạo ví
+    pub rp_id_hash: [u8; 32],            // SHA256 của WebAuthn relying party ID
+    pub address_seed: [u8; 32],          // Seed thứ hai của PDA ví
+    pub version: u8,                     // ACCOUNT_VERSION
+    pub bump: u8,
+    pub threshold: u8,
+    pub guardian_count: u8,              // Số ô đã dùng trong `guardians`
+    pub active_guardian_count: u8,
+    pub owner_count: u8,
+    pub approver_count: u8,
+    pub padding: [u8; 1],
+    pub guardians: [GuardianSlot; MAX_LARGE_GUARDIANS],
+    pub reserved: [u8; 64],
+}
+
+/// Một guardian trong bảng của `LargeMultiSigWallet`
+#[zero_copy]
+pub struct GuardianSlot {
+    pub guardian_id: u64,
+    pub nonce: u64,                      // Tăng sau mỗi chữ ký hợp lệ, nằm trong payload ký
+    pub sign_count: u32,                 // signCount WebAuthn gần nhất, chỉ dùng cho passkey
+    pub auth_kind: u8,                   // AUTH_KIND_*
+    pub permissions: u8,                 // Bitmask PERMISSION_*
+    pub flags: u8,                       // SLOT_FLAG_*
+    pub padding: [u8; 1],
+    pub auth_key: [u8; 33],              // Khóa theo `auth_kind`, căn trái
+    pub padding2: [u8; 7],
+}
+
+impl LargeMultiSigWallet {
+    pub fn slots(&self) -> &[GuardianSlot] {
+        &self.guardians[..self.guardian_count as usize]
+    }
+    
+    /// Chỉ số ô của guardian, cũng là vị trí bit của guardian trong bitmap phê duyệt
+    pub fn slot_index(&self, guardian_id: u64) -> Result<usize> {
+        self.slots()
+            .iter()
+            .position(|slot| slot.guardian_id == guardian_id)
+            .ok_or(WalletError::InvalidGuardian.into())
+    }
+    
+    /// Cùng bất biến với `MultiSigWallet::check_invariants`
+    pub fn check_invariants(&self) -> Result<()> {
+        require!(
+            self.threshold > 0 && self.threshold as usize <= MAX_LARGE_GUARDIANS,
+            WalletError::InvalidThreshold
+        );
+        require!(
+            self.threshold <= self.active_guardian_count,
+            WalletError::InvalidThreshold
+        );
+        require!(
+            self.threshold <= self.approver_count,
+            WalletError::InvalidThreshold
+        );
+        require!(self.owner_count > 0, WalletError::NoActiveOwner);
+        Ok(())
+    }
+}
+
+impl GuardianSlot {
+    pub fn is_active(&self) -> bool {
+        self.flags & SLOT_FLAG_ACTIVE != 0
+    }
+    
+    pub fn is_owner(&self) -> bool {
+        self.flags & SLOT_FLAG_OWNER != 0
+    }
+    
+    pub fn has_permission(&self, permission: u8) -> bool {
+        self.permissions & permission == permission
+    }
+    
+    /// Giống `Guardian::is_approver`
+    pub fn is_approver(&self) -> bool {
+        self.is_active() && self.auth_kind != AUTH_KIND_NONE && self.has_permission(PERMISSION_APPROVE)
+    }
+    
+    pub fn auth(&self) -> Option<GuardianAuth> {
+        match self.auth_kind {
+            AUTH_KIND_PASSKEY => Some(GuardianAuth::Passkey(self.auth_key)),
+            AUTH_KIND_ED25519 => {
+                let mut pubkey = [0u8; 32];
+                pubkey.copy_from_slice(&self.auth_key[..32]);
+                Some(GuardianAuth::Ed25519(pubkey))
+            },
+            AUTH_KIND_SIGNER => Some(GuardianAuth::Signer(Pubkey::try_from(&self.auth_key[..32]).ok()?)),
+            _ => None,
+        }
+    }
+    
+    pub fn set_auth(&mut self, auth: GuardianAuth) {
+        self.auth_kind = match auth {
+            GuardianAuth::Passkey(_) => AUTH_KIND_PASSKEY,
+            GuardianAuth::Ed25519(_) => AUTH_KIND_ED25519,
+            GuardianAuth::Signer(_) => AUTH_KIND_SIGNER,
+        };
+        let key = auth.key_bytes();
+        self.auth_key = [0; 33];
+        self.auth_key[..key.len()].copy_from_slice(key);
+    }
+}
+
+/// Đề xuất chuyển SOL của `LargeMultiSigWallet`. Phê duyệt được ghi vào bitmap theo chỉ số ô
+/// guardian thay vì mỗi phiếu một PDA `ProposalSignature`.
+/// PDA: [b"large_proposal", wallet, proposal_id]
+#[account(zero_copy)]
+pub struct LargeProposal {
+    pub wallet: Pubkey,
+    pub destination: Pubkey,
+    pub proposer: Pubkey,
+    pub proposal_id: u64,
+    pub amount: u64,                     // Lamports
+    pub created_at: i64,
+    pub executed_at: i64,                // 0 khi chưa thực thi
+    pub approvals: u64,                  // Bit i: guardian ở ô i đã phê duyệt
+    pub version: u8,                     // ACCOUNT_VERSION
+    pub status: u8,                      // LARGE_PROPOSAL_*
+    pub signatures_count: u8,
+    pub required_signatures: u8,
+    pub bump: u8,
+    pub padding: [u8; 3],
+    pub reserved: [u8; 32],
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use crate::state::wallet::ActionParams;
+
+// Layout phiên bản 1 của các tài khoản, trước khi có byte version.
+// Chỉ dùng để đọc dữ liệu cũ trong `migrate_account`; discriminator không đổi.
+
+#[derive(AnchorDeserialize)]
+#[cfg_attr(test, derive(AnchorSerialize))]
+pub struct MultiSigWalletV1 {
+    pub threshold: u8,
+    pub guardian_count: u8,
+    pub recovery_nonce: u64,
+    pub bump: u8,
+    pub transaction_nonce: u64,
+    pub last_transaction_timestamp: i64,
+    pub owner: Pubkey,
+    pub credential_id: String,
+}
+
+#[derive(AnchorDeserialize)]
+#[cfg_attr(test, derive(AnchorSerialize))]
+pub struct GuardianV1 {
+    pub wallet: Pubkey,
+    pub guardian_id: u64,
+    pub name: String,
+    pub is_active: bool,
+    pub recovery_hash: [u8; 32],
+    pub is_owner: bool,
+    pub webauthn_pubkey: Option<[u8; 33]>,
+    pub bump: u8,
+}
+
+#[derive(AnchorDeserialize)]
+#[cfg_attr(test, derive(AnchorSerialize))]
+pub struct TransactionProposalV1 {
+    pub multisig: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub description: String,
+    pub action: String,
+    pub params: ActionParams,
+    pub status: ProposalStatusV1,
+    pub created_at: i64,
+    pub executed_at: Option<i64>,
+    pub signatures_count: u8,
+    pub required_signatures: u8,
+    pub bump: u8,
+}
+
+#[derive(AnchorDeserialize)]
+#[cfg_attr(test, derive(AnchorSerialize))]
+pub struct ProposalSignatureV1 {
+    pub proposal: Pubkey,
+    pub guardian_id: u64,
+    pub signature_time: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorDeserialize)]
+#[cfg_attr(test, derive(AnchorSerialize))]
+pub enum ProposalStatusV1 {
+    Pending,
+    Executed,
+    Rejected,
+    Expired,
+}
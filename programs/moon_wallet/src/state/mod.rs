@@ -1,7 +1,18 @@
 pub mod wallet;
 pub mod guardian;
 pub mod proposal;
+pub mod recovery;
+pub mod device;
+pub mod version;
+pub mod legacy;
+#[cfg(feature = "experimental-large-wallet")]
+pub mod large_wallet;
 
 pub use guardian::*;
 pub use wallet::*;
-pub use proposal::*; 
\ No newline at end of file
+pub use proposal::*;
+pub use recovery::*;
+pub use device::*;
+pub use version::*;
+#[cfg(feature = "experimental-large-wallet")]
+pub use large_wallet::*;
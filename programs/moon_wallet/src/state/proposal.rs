@@ -1,8 +1,16 @@
 use anchor_lang::prelude::*;
-use crate::state::wallet::ActionParams;
+use crate::state::wallet::{ActionParams, ConfigAction};
+use crate::auth::GuardianProof;
+use crate::errors::WalletError;
+use crate::state::version::{PROPOSAL_RESERVED_LEN, SIGNATURE_RESERVED_LEN};
 
+// Số bước tối đa trong một đề xuất "batch"
+pub const MAX_BATCH_ACTIONS: usize = 10;
+
+/// Kích thước phụ thuộc nội dung nên không dùng InitSpace, xem `TransactionProposal::space_for`
 #[account]
 pub struct TransactionProposal {
+    pub version: u8,                  // ACCOUNT_VERSION
     pub multisig: Pubkey,             
     pub proposal_id: u64,             
     pub proposer: Pubkey,             
@@ -13,22 +21,203 @@ pub struct TransactionProposal {
     pub created_at: i64,              
     pub executed_at: Option<i64>,     
     pub signatures_count: u8,         
+    pub rejections_count: u8,         
     pub required_signatures: u8,      
+    pub approver_count: u8,           // Số guardian có quyền phê duyệt của ví lúc tạo đề xuất
     pub bump: u8,                     
+    pub instructions: Vec<ProposalInstruction>, // Danh sách instruction CPI cho action "invoke"
+    pub actions: Vec<ProposalAction>,           // Các bước của action "batch", thực thi theo thứ tự
+    pub expires_at: Option<i64>,                // Sau thời điểm này đề xuất không thể phê duyệt/thực thi
+    pub config_action: Option<ConfigAction>,    // Thay đổi cấu hình ví cho action "config"
+    pub weighted: bool,                         // Ngưỡng tính theo trọng số, chụp từ ví lúc tạo đề xuất
+    pub approval_weight: u16,                   // Tổng trọng số các phiếu phê duyệt
+    pub rejection_weight: u16,                  // Tổng trọng số các phiếu từ chối
+    pub total_weight: u16,                      // Tổng trọng số người phê duyệt của ví lúc tạo đề xuất
+    pub reserved: [u8; PROPOSAL_RESERVED_LEN],
+}
+
+impl TransactionProposal {
+    /// Kích thước tài khoản (kể cả discriminator) cho một đề xuất với nội dung đã cho
+    pub fn space_for(
+        description: &str,
+        action: &str,
+        instructions: &[ProposalInstruction],
+        actions: &[ProposalAction],
+        config_action: Option<&ConfigAction>,
+    ) -> usize {
+        8 + 
+        1 + 
+        32 + 
+        8 + 
+        32 + 
+        4 + description.len() + 
+        4 + action.len() + 
+        ActionParams::INIT_SPACE + 
+        ProposalStatus::INIT_SPACE + 
+        8 + 
+        1 + 8 + 
+        1 + 
+        1 + 
+        1 + 
+        1 + 
+        1 + 
+        4 + instructions.iter().map(|ix| ix.space()).sum::<usize>() + 
+        4 + actions.iter().map(|step| step.space()).sum::<usize>() + 
+        1 + 8 + 
+        1 + config_action.map_or(0, |config| config.space()) + 
+        1 + 
+        2 + 
+        2 + 
+        2 + 
+        PROPOSAL_RESERVED_LEN
+    }
+    
+    pub fn space(&self) -> usize {
+        Self::space_for(
+            &self.description,
+            &self.action,
+            &self.instructions,
+            &self.actions,
+            self.config_action.as_ref(),
+        )
+    }
+    
+    pub fn is_expired(&self, now: i64) -> bool {
+        matches!(self.expires_at, Some(expires_at) if now >= expires_at)
+    }
+    
+    /// Đủ phê duyệt: tổng trọng số với đề xuất tính theo trọng số, số chữ ký với đề xuất thường
+    pub fn has_quorum(&self) -> bool {
+        if self.weighted {
+            self.approval_weight >= self.required_signatures as u16
+        } else {
+            self.signatures_count >= self.required_signatures
+        }
+    }
+    
+    /// Phần phiếu chưa từ chối của những guardian có thể phê duyệt lúc tạo đề xuất còn đủ để đạt ngưỡng
+    pub fn can_reach_quorum(&self) -> bool {
+        if self.weighted {
+            self.total_weight.saturating_sub(self.rejection_weight) >= self.required_signatures as u16
+        } else {
+            self.approver_count.saturating_sub(self.rejections_count) >= self.required_signatures
+        }
+    }
+    
+    pub fn add_vote(&mut self, approved: bool, weight: u8) -> Result<()> {
+        if approved {
+            self.signatures_count = self.signatures_count.checked_add(1).ok_or(WalletError::ArithmeticOverflow)?;
+            self.approval_weight = self.approval_weight.checked_add(weight as u16).ok_or(WalletError::ArithmeticOverflow)?;
+        } else {
+            self.rejections_count = self.rejections_count.checked_add(1).ok_or(WalletError::ArithmeticOverflow)?;
+            self.rejection_weight = self.rejection_weight.checked_add(weight as u16).ok_or(WalletError::ArithmeticOverflow)?;
+        }
+        Ok(())
+    }
+    
+    /// Bỏ một phiếu đã ghi khi guardian đổi phiếu, `weight` là trọng số lưu trong phiếu đó
+    pub fn remove_vote(&mut self, approved: bool, weight: u8) -> Result<()> {
+        if approved {
+            self.signatures_count = self.signatures_count.checked_sub(1).ok_or(WalletError::ArithmeticOverflow)?;
+            self.approval_weight = self.approval_weight.checked_sub(weight as u16).ok_or(WalletError::ArithmeticOverflow)?;
+        } else {
+            self.rejections_count = self.rejections_count.checked_sub(1).ok_or(WalletError::ArithmeticOverflow)?;
+            self.rejection_weight = self.rejection_weight.checked_sub(weight as u16).ok_or(WalletError::ArithmeticOverflow)?;
+        }
+        Ok(())
+    }
+    
+    /// Tổng khoản chi (mint, số tiền) của đề xuất, dùng để chọn mức timelock; mint None là SOL.
+    /// Trả về None nếu không xác định được (CPI tùy ý, thay đổi cấu hình, batch trộn nhiều loại token).
+    pub fn total_spend(&self) -> Option<(Option<Pubkey>, u64)> {
+        match self.action.as_str() {
+            "transfer" | "token_transfer" => Some((spend_mint(&self.action, &self.params), self.params.amount?)),
+            "batch" => {
+                let mut total: Option<(Option<Pubkey>, u64)> = None;
+                for step in self.actions.iter() {
+                    let Some(amount) = step.params.amount else { continue };
+                    let mint = spend_mint(&step.action, &step.params);
+                    total = match total {
+                        None => Some((mint, amount)),
+                        Some((total_mint, total_amount)) if total_mint == mint => {
+                            Some((mint, total_amount.checked_add(amount)?))
+                        },
+                        Some(_) => return None,
+                    };
+                }
+                Some(total.unwrap_or((None, 0)))
+            },
+            _ => None,
+        }
+    }
+}
+
+fn spend_mint(action: &str, params: &ActionParams) -> Option<Pubkey> {
+    match action {
+        "token_transfer" => params.token_mint,
+        _ => None,
+    }
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct ProposalSignature {
+    pub version: u8,                  // ACCOUNT_VERSION
     pub proposal: Pubkey,             
     pub guardian_id: u64,             
     pub signature_time: i64,          
     pub bump: u8,                     
+    pub approved: bool,               // true: phê duyệt, false: từ chối
+    pub weight: u8,                   // Trọng số của guardian lúc bỏ phiếu
+    pub reserved: [u8; SIGNATURE_RESERVED_LEN],
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+/// Một phê duyệt trong `batch_approve`, mỗi guardian ký challenge của riêng mình
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GuardianApproval {
+    pub guardian_id: u64,
+    pub timestamp: i64,
+    pub proof: GuardianProof,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, InitSpace)]
 pub enum ProposalStatus {
     Pending,        
     Executed,       
     Rejected,       
     Expired,        
-} 
\ No newline at end of file
+    Cancelled,      
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProposalAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProposalInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<ProposalAccountMeta>,
+    pub data: Vec<u8>,
+}
+
+impl ProposalInstruction {
+    // Kích thước Borsh của instruction khi lưu trong đề xuất
+    pub fn space(&self) -> usize {
+        32 + 4 + self.accounts.len() * (32 + 1 + 1) + 4 + self.data.len()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProposalAction {
+    pub action: String,
+    pub params: ActionParams,
+}
+
+impl ProposalAction {
+    pub fn space(&self) -> usize {
+        4 + self.action.len() + ActionParams::INIT_SPACE
+    }
+}
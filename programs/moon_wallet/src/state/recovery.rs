@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::state::version::{RECORD_RESERVED_LEN, RECOVERY_RESERVED_LEN};
+
+// Thời gian thử thách của yêu cầu khôi phục: 48 giờ
+pub const RECOVERY_CHALLENGE_PERIOD: i64 = 48 * 60 * 60;
+// Yêu cầu khôi phục chưa hoàn tất sau 7 ngày kể từ khi mở thì hết hạn
+pub const RECOVERY_REQUEST_TTL: i64 = 7 * 24 * 60 * 60;
+
+#[account]
+#[derive(InitSpace)]
+pub struct RecoveryRequest {
+    pub version: u8,                  // ACCOUNT_VERSION
+    pub multisig: Pubkey,             
+    pub recovery_nonce: u64,          // Phải trùng multisig.recovery_nonce, nếu không yêu cầu đã cũ
+    pub old_guardian_id: u64,         
+    pub new_guardian_id: u64,         
+    pub new_webauthn_pubkey: [u8; 33],
+    pub new_recovery_hash: [u8; 32],  // Recovery hash mới của owner mới, preimage cũ đã lộ khi mở yêu cầu
+    pub created_at: i64,              
+    pub executable_at: i64,           // Hết thời gian thử thách
+    pub expires_at: i64,              // Sau thời điểm này yêu cầu không thể phê duyệt/hoàn tất
+    pub approvals_count: u8,          
+    pub required_approvals: u8,       // Số phiếu, hoặc tổng trọng số khi `weighted`
+    pub status: RecoveryStatus,       
+    pub bump: u8,                     
+    pub weighted: bool,               // Ngưỡng tính theo trọng số, chụp từ ví lúc mở yêu cầu
+    pub approval_weight: u16,         // Tổng trọng số các phiếu phê duyệt
+    pub reserved: [u8; RECOVERY_RESERVED_LEN],
+}
+
+impl RecoveryRequest {
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+    
+    /// Đủ phê duyệt: tổng trọng số với yêu cầu tính theo trọng số, số phiếu với yêu cầu thường
+    pub fn has_quorum(&self) -> bool {
+        if self.weighted {
+            self.approval_weight >= self.required_approvals as u16
+        } else {
+            self.approvals_count >= self.required_approvals
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RecoveryApproval {
+    pub version: u8,                  // ACCOUNT_VERSION
+    pub request: Pubkey,              
+    pub guardian_id: u64,             
+    pub approved_at: i64,             
+    pub bump: u8,                     
+    pub payer: Pubkey,                // Người trả rent, nhận lại khi phiếu được đóng
+    pub reserved: [u8; RECORD_RESERVED_LEN],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, InitSpace)]
+pub enum RecoveryStatus {
+    Pending,        
+    Executed,       
+    Cancelled,      
+}
@@ -0,0 +1,13 @@
+// Phiên bản layout của mọi tài khoản chương trình, lưu ở byte đầu tiên sau discriminator.
+// Phiên bản 1 là layout ban đầu, chưa có trường version (xem `state::legacy`),
+// chỉ được đọc bởi `migrate_account`.
+pub const ACCOUNT_VERSION: u8 = 2;
+
+// Số byte dự trữ ở cuối tài khoản. Trường mới được cắt ra từ phần dự trữ
+// để tài khoản cũ vẫn deserialize được mà không cần realloc.
+pub const WALLET_RESERVED_LEN: usize = 61;      // 64, đã dùng 3 cho bỏ phiếu theo trọng số
+pub const GUARDIAN_RESERVED_LEN: usize = 31;    // 32, đã dùng 1 cho `weight`
+pub const PROPOSAL_RESERVED_LEN: usize = 25;    // 32, đã dùng 7 cho bỏ phiếu theo trọng số
+pub const SIGNATURE_RESERVED_LEN: usize = 15;   // 16, đã dùng 1 cho `weight`
+pub const RECOVERY_RESERVED_LEN: usize = 13;    // 16, đã dùng 3 cho bỏ phiếu theo trọng số
+pub const RECORD_RESERVED_LEN: usize = 16;
@@ -1,21 +1,651 @@
 use anchor_lang::prelude::*;
+use crate::errors::WalletError;
+use crate::state::guardian::{validate_permissions, validate_weight, Guardian, GuardianAuth, MAX_GUARDIANS, MAX_GUARDIAN_NAME_LEN};
+use crate::state::version::WALLET_RESERVED_LEN;
 
+// Số mức timelock tối đa theo số tiền
+pub const MAX_TIMELOCK_TIERS: usize = 4;
+// Độ trễ tối đa: 30 ngày
+pub const MAX_TIMELOCK_DELAY: i64 = 30 * 24 * 60 * 60;
+// Số hạn mức chi tiêu (theo mint) tối đa
+pub const MAX_SPENDING_LIMITS: usize = 4;
+// Số địa chỉ tối đa trong danh sách đích được phép
+pub const MAX_ALLOWLIST_DESTINATIONS: usize = 8;
+// Độ dài tối đa của credential ID
+pub const MAX_CREDENTIAL_ID_LEN: usize = 64;
+
+// Cách dẫn xuất seed của PDA ví, xem `instructions::wallet::multisig_address_seed`
+pub const SEED_VERSION_LEGACY: u8 = 0;   // Credential ID gập XOR về 24 byte, có thể bị trùng
+pub const SEED_VERSION_V1: u8 = 1;       // SHA256(credential ID || owner || salt)
 
 #[account]
+#[derive(InitSpace)]
 pub struct MultiSigWallet {
+    pub version: u8,                 // ACCOUNT_VERSION
     pub threshold: u8,               
     pub guardian_count: u8,          
+    pub active_guardian_count: u8,   // Số guardian đang hoạt động
+    pub owner_count: u8,             // Số owner đang hoạt động
+    pub approver_count: u8,          // Số guardian đang hoạt động có quyền phê duyệt
     pub recovery_nonce: u64,         
     pub bump: u8,                    
     pub transaction_nonce: u64,      
     pub last_transaction_timestamp: i64, 
-    pub owner: Pubkey,               // Public key của người tạo ví
+    pub owner: Pubkey,               // Public key của người tạo ví, là một phần seed của PDA ví
+    #[max_len(MAX_CREDENTIAL_ID_LEN)]
     pub credential_id: String,       // Credential ID từ WebAuthn, dùng làm seed
+    pub timelock: TimelockConfig,    // Độ trễ trước khi đề xuất được thực thi
+    pub spending_policy: SpendingPolicy, // Hạn mức cho giao dịch chỉ cần chữ ký owner
+    pub rp_id_hash: [u8; 32],        // SHA256 của WebAuthn relying party ID
+    pub seed_version: u8,            // SEED_VERSION_*
+    pub salt: [u8; 32],              // Salt của người tạo ví, bằng 0 với ví seed cũ
+    pub migrated_from: Option<Pubkey>, // Địa chỉ ví seed cũ nếu ví được chuyển từ đó
+    pub pending_proposals: u16,      // Số đề xuất đang chờ (Pending)
+    #[max_len(MAX_GUARDIANS)]
+    pub guardians: Vec<GuardianEntry>, // Danh sách guardian hiện có
+    pub proposal_count: u64,         // Số đề xuất đã tạo, cũng là ID của đề xuất tiếp theo
+    pub weighted_voting: bool,       // true: `threshold` là tổng trọng số thay vì số guardian
+    pub approver_weight: u16,        // Tổng trọng số của các guardian có thể phê duyệt
+    pub reserved: [u8; WALLET_RESERVED_LEN],
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+impl MultiSigWallet {
+    /// Bất biến của ví sau mỗi thay đổi guardian/ngưỡng: ngưỡng không vượt quá
+    /// số guardian (hoặc tổng trọng số, khi bỏ phiếu theo trọng số) đang hoạt động có quyền
+    /// phê duyệt và luôn còn ít nhất một owner đang hoạt động.
+    pub fn check_invariants(&self) -> Result<()> {
+        require!(self.threshold > 0, WalletError::InvalidThreshold);
+        if self.weighted_voting {
+            require!(
+                self.threshold as u16 <= self.approver_weight,
+                WalletError::InvalidThreshold
+            );
+        } else {
+            require!(
+                self.threshold <= MAX_GUARDIANS,
+                WalletError::InvalidThreshold
+            );
+            require!(
+                self.threshold <= self.active_guardian_count,
+                WalletError::InvalidThreshold
+            );
+            require!(
+                self.threshold <= self.approver_count,
+                WalletError::InvalidThreshold
+            );
+        }
+        require!(self.owner_count > 0, WalletError::NoActiveOwner);
+        Ok(())
+    }
+    
+    /// Ghi lại trạng thái hiện tại của guardian vào danh sách guardian của ví
+    pub fn sync_guardian_entry(&mut self, guardian: &Guardian) -> Result<()> {
+        let entry = GuardianEntry::from(guardian);
+        match self.guardians.iter_mut().find(|existing| existing.guardian_id == guardian.guardian_id) {
+            Some(existing) => *existing = entry,
+            None => {
+                require!(self.guardians.len() < MAX_GUARDIANS as usize, WalletError::LimitExceeded);
+                self.guardians.push(entry);
+            }
+        }
+        Ok(())
+    }
+    
+    /// Một đề xuất rời trạng thái Pending: được thực thi, bị từ chối, bị hủy hoặc hết hạn
+    pub fn settle_proposal(&mut self) {
+        self.pending_proposals = self.pending_proposals.saturating_sub(1);
+    }
+    
+    pub fn remove_guardian_entry(&mut self, guardian_id: u64) {
+        self.guardians.retain(|entry| entry.guardian_id != guardian_id);
+    }
+    
+    /// Cập nhật approver_count và approver_weight theo `Guardian::approver_weight`
+    /// trước và sau khi thay đổi guardian. Ví tạo trước khi có trọng số có approver_weight bằng 0,
+    /// giá trị này được tính lại khi bật bỏ phiếu theo trọng số.
+    pub fn update_approvers(&mut self, was_weight: u16, is_weight: u16) {
+        match (was_weight > 0, is_weight > 0) {
+            (false, true) => self.approver_count += 1,
+            (true, false) => self.approver_count -= 1,
+            _ => {}
+        }
+        self.approver_weight = self.approver_weight.saturating_sub(was_weight).saturating_add(is_weight);
+    }
+}
+
+/// Bản tóm tắt một guardian trong danh sách của ví, để client liệt kê guardian mà không cần quét PDA
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct GuardianEntry {
+    pub guardian_id: u64,
+    pub permissions: u8,
+    pub is_active: bool,
+    pub is_owner: bool,
+}
+
+impl From<&Guardian> for GuardianEntry {
+    fn from(guardian: &Guardian) -> Self {
+        Self {
+            guardian_id: guardian.guardian_id,
+            permissions: guardian.permissions,
+            is_active: guardian.is_active,
+            is_owner: guardian.is_owner,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct ActionParams {
     pub amount: Option<u64>,        
     pub destination: Option<Pubkey>, 
     pub token_mint: Option<Pubkey>, 
-}
\ No newline at end of file
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct TimelockTier {
+    pub mint: Option<Pubkey>,       // None: SOL, mức chỉ áp dụng cho số tiền cùng loại
+    pub min_amount: u64,            // Áp dụng khi số tiền >= min_amount
+    pub delay: i64,                 
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct TimelockConfig {
+    pub delay: i64,                 // Độ trễ mặc định (giây)
+    #[max_len(MAX_TIMELOCK_TIERS)]
+    pub tiers: Vec<TimelockTier>,   // Với cùng mint, sắp xếp tăng dần theo min_amount
+}
+
+impl TimelockConfig {
+    /// Độ trễ áp dụng cho một khoản chi (mint, số tiền). Khi không xác định được khoản chi
+    /// (ví dụ CPI tùy ý, hoặc batch trộn nhiều loại token) thì dùng độ trễ lớn nhất.
+    pub fn delay_for(&self, spend: Option<(Option<Pubkey>, u64)>) -> i64 {
+        match spend {
+            Some((mint, amount)) => self
+                .tiers
+                .iter()
+                .filter(|tier| tier.mint == mint && amount >= tier.min_amount)
+                .map(|tier| tier.delay)
+                .fold(self.delay, i64::max),
+            None => self
+                .tiers
+                .iter()
+                .map(|tier| tier.delay)
+                .fold(self.delay, i64::max),
+        }
+    }
+    
+    pub fn validate(&self) -> Result<()> {
+        require!(self.tiers.len() <= MAX_TIMELOCK_TIERS, WalletError::LimitExceeded);
+        require!(
+            (0..=MAX_TIMELOCK_DELAY).contains(&self.delay),
+            WalletError::InvalidTimelock
+        );
+        for (i, tier) in self.tiers.iter().enumerate() {
+            require!(
+                (0..=MAX_TIMELOCK_DELAY).contains(&tier.delay),
+                WalletError::InvalidTimelock
+            );
+            if let Some(previous) = self.tiers[..i].iter().rev().find(|other| other.mint == tier.mint) {
+                require!(
+                    tier.min_amount > previous.min_amount,
+                    WalletError::InvalidTimelock
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct SpendingLimit {
+    pub mint: Option<Pubkey>,       // None: SOL
+    pub per_transaction_cap: u64,   
+    pub allowance: u64,             // Tổng được chi tối đa, phần đã dùng hồi lại dần trong `period`
+    pub period: i64,                // Thời gian (giây) để hạn mức hồi lại hoàn toàn, ví dụ 86400 hoặc 604800
+    pub spent: u64,                 // Phần hạn mức đang bị chiếm tại `updated_at`
+    pub updated_at: i64,            // Thời điểm ghi nhận khoản chi gần nhất
+}
+
+impl SpendingLimit {
+    /// Phần hạn mức còn bị chiếm tại `now`. Hạn mức hồi lại tuyến tính với tốc độ
+    /// `allowance / period` mỗi giây, nên không thể chi gấp đôi ở ranh giới giữa hai chu kỳ
+    /// như khi xóa `spent` theo chu kỳ cố định.
+    pub fn outstanding(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.updated_at).max(0) as u128;
+        let recovered = (self.allowance as u128 * elapsed / self.period.max(1) as u128).min(u64::MAX as u128) as u64;
+        self.spent.saturating_sub(recovered)
+    }
+    
+    /// Ghi nhận một khoản chi vào hạn mức, trả về phần hạn mức còn lại
+    pub fn consume(&mut self, amount: u64, now: i64) -> Result<u64> {
+        require!(amount <= self.per_transaction_cap, WalletError::SpendingLimitExceeded);
+        
+        let spent = self.outstanding(now).checked_add(amount).ok_or(WalletError::ArithmeticOverflow)?;
+        require!(spent <= self.allowance, WalletError::SpendingLimitExceeded);
+        self.spent = spent;
+        self.updated_at = now;
+        Ok(self.allowance - spent)
+    }
+}
+
+/// Hạn mức cho `mint`. Mint chưa có hạn mức thì không được chi trực tiếp.
+pub fn find_spending_limit(limits: &mut [SpendingLimit], mint: Option<Pubkey>) -> Result<&mut SpendingLimit> {
+    limits
+        .iter_mut()
+        .find(|limit| limit.mint == mint)
+        .ok_or(WalletError::SpendingLimitExceeded.into())
+}
+
+/// Mỗi mint một hạn mức, chu kỳ dương và hạn mức mỗi giao dịch không vượt tổng hạn mức
+pub fn validate_spending_limits(limits: &[SpendingLimit]) -> Result<()> {
+    require!(limits.len() <= MAX_SPENDING_LIMITS, WalletError::LimitExceeded);
+    for (i, limit) in limits.iter().enumerate() {
+        require!(limit.period > 0, WalletError::InvalidConfig);
+        require!(limit.per_transaction_cap <= limit.allowance, WalletError::InvalidConfig);
+        require!(
+            limits[..i].iter().all(|other| other.mint != limit.mint),
+            WalletError::InvalidConfig
+        );
+    }
+    Ok(())
+}
+
+/// Giữ lại phần hạn mức đang bị chiếm khi thay danh sách hạn mức, để việc đổi cấu hình không xóa hạn mức đã dùng
+pub fn carry_over_spending(limits: &mut [SpendingLimit], previous: &[SpendingLimit], now: i64) {
+    for limit in limits.iter_mut() {
+        let old = previous.iter().find(|old| old.mint == limit.mint);
+        limit.spent = old.map_or(0, |old| old.outstanding(now));
+        limit.updated_at = now;
+    }
+}
+
+/// Chính sách chi tiêu cho verify_and_execute. Ví mới bắt đầu với `SpendingPolicy::restricted`;
+/// giao dịch vượt hạn mức (hoặc mint chưa có hạn mức) phải đi qua đề xuất multisig.
+/// Khi chính sách tắt (`enabled = false`), guardian có PERMISSION_SPEND không thể chi trực tiếp.
+/// `limits` là trần chung của cả ví; mỗi guardian còn bị giới hạn bởi `Guardian::spending_limits` của mình.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct SpendingPolicy {
+    pub enabled: bool,
+    #[max_len(MAX_SPENDING_LIMITS)]
+    pub limits: Vec<SpendingLimit>,
+    #[max_len(MAX_ALLOWLIST_DESTINATIONS)]
+    pub allowlist: Vec<Pubkey>,     // Rỗng: cho phép mọi địa chỉ đích
+}
+
+impl SpendingPolicy {
+    /// Chính sách mặc định: bật nhưng chưa có hạn mức nào, nên mọi giao dịch chỉ cần
+    /// chữ ký owner đều bị từ chối cho tới khi ví cấu hình hạn mức qua đề xuất
+    pub fn restricted() -> Self {
+        Self {
+            enabled: true,
+            limits: Vec::new(),
+            allowlist: Vec::new(),
+        }
+    }
+    
+    pub fn validate(&self) -> Result<()> {
+        require!(self.limits.len() <= MAX_SPENDING_LIMITS, WalletError::LimitExceeded);
+        require!(self.allowlist.len() <= MAX_ALLOWLIST_DESTINATIONS, WalletError::LimitExceeded);
+        validate_spending_limits(&self.limits)
+    }
+    
+    /// Kiểm tra và ghi nhận một khoản chi vào trần chung của ví. Trả lỗi nếu vượt hạn mức,
+    /// khi đó giao dịch phải được thực hiện qua create_proposal/approve_proposal.
+    pub fn consume(&mut self, mint: Option<Pubkey>, amount: u64, destination: Pubkey, now: i64) -> Result<()> {
+        require!(self.enabled, WalletError::SpendingLimitExceeded);
+        
+        require!(
+            self.allowlist.is_empty() || self.allowlist.contains(&destination),
+            WalletError::DestinationNotAllowed
+        );
+        
+        let remaining = find_spending_limit(&mut self.limits, mint)?.consume(amount, now)?;
+        msg!("Hạn mức còn lại của ví: {}", remaining);
+        Ok(())
+    }
+}
+
+/// Thay đổi cấu hình ví, chỉ được áp dụng qua đề xuất "config" đã đủ ngưỡng
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum ConfigAction {
+    SetTimelock(TimelockConfig),
+    SetSpendingPolicy(SpendingPolicy),
+    AddGuardian {
+        guardian_id: u64,
+        name: String,
+        recovery_hash: [u8; 32],    // Đã hash sẵn, không lưu recovery_hash_intermediate on-chain
+        is_owner: bool,
+        auth: GuardianAuth,
+        permissions: u8,
+        weight: u8,
+    },
+    RemoveGuardian {
+        guardian_id: u64,
+    },
+    SetGuardianStatus {
+        guardian_id: u64,
+        is_active: bool,
+    },
+    SetGuardianOwner {
+        guardian_id: u64,
+        is_owner: bool,
+    },
+    ChangeThreshold {
+        threshold: u8,
+    },
+    SetRpIdHash {
+        rp_id_hash: [u8; 32],
+    },
+    SetGuardianPermissions {
+        guardian_id: u64,
+        permissions: u8,
+    },
+    /// Chuyển ví seed cũ sang PDA mới dẫn xuất từ `salt`
+    MigrateSeeds {
+        salt: [u8; 32],
+    },
+    SetGuardianWeight {
+        guardian_id: u64,
+        weight: u8,
+    },
+    /// Bật/tắt bỏ phiếu theo trọng số. Ngưỡng giữ nguyên và phải thỏa bất biến của chế độ mới.
+    SetWeightedVoting {
+        enabled: bool,
+    },
+    /// Hạn mức chi trực tiếp riêng của một guardian, phần đã dùng của cùng mint được giữ lại
+    SetGuardianSpendingLimits {
+        guardian_id: u64,
+        limits: Vec<SpendingLimit>,
+    },
+}
+
+impl ConfigAction {
+    pub fn space(&self) -> usize {
+        match self {
+            ConfigAction::SetTimelock(_) => 1 + TimelockConfig::INIT_SPACE,
+            ConfigAction::SetSpendingPolicy(_) => 1 + SpendingPolicy::INIT_SPACE,
+            ConfigAction::AddGuardian { name, .. } => 1 + 8 + 4 + name.len() + 32 + 1 + GuardianAuth::INIT_SPACE + 1 + 1,
+            ConfigAction::RemoveGuardian { .. } => 1 + 8,
+            ConfigAction::SetGuardianStatus { .. } => 1 + 8 + 1,
+            ConfigAction::SetGuardianOwner { .. } => 1 + 8 + 1,
+            ConfigAction::ChangeThreshold { .. } => 1 + 1,
+            ConfigAction::SetRpIdHash { .. } => 1 + 32,
+            ConfigAction::SetGuardianPermissions { .. } => 1 + 8 + 1,
+            ConfigAction::MigrateSeeds { .. } => 1 + 32,
+            ConfigAction::SetGuardianWeight { .. } => 1 + 8 + 1,
+            ConfigAction::SetWeightedVoting { .. } => 1 + 1,
+            ConfigAction::SetGuardianSpendingLimits { limits, .. } => 1 + 8 + 4 + limits.len() * SpendingLimit::INIT_SPACE,
+        }
+    }
+    
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            ConfigAction::SetTimelock(config) => config.validate(),
+            ConfigAction::SetSpendingPolicy(policy) => policy.validate(),
+            ConfigAction::AddGuardian { name, is_owner, auth, permissions, weight, .. } => {
+                require!(name.len() <= MAX_GUARDIAN_NAME_LEN, WalletError::NameTooLong);
+                if *is_owner {
+                    require!(matches!(auth, GuardianAuth::Passkey(_)), WalletError::WebAuthnNotConfigured);
+                }
+                validate_permissions(*permissions)?;
+                validate_weight(*weight)
+            },
+            ConfigAction::SetGuardianPermissions { permissions, .. } => validate_permissions(*permissions),
+            ConfigAction::ChangeThreshold { threshold } => {
+                require!(*threshold > 0, WalletError::InvalidThreshold);
+                Ok(())
+            },
+            ConfigAction::SetGuardianWeight { weight, .. } => validate_weight(*weight),
+            ConfigAction::SetGuardianSpendingLimits { limits, .. } => validate_spending_limits(limits),
+            ConfigAction::RemoveGuardian { .. } 
+            | ConfigAction::SetGuardianStatus { .. } 
+            | ConfigAction::SetGuardianOwner { .. } 
+            | ConfigAction::SetRpIdHash { .. } 
+            | ConfigAction::MigrateSeeds { .. }
+            | ConfigAction::SetWeightedVoting { .. } => Ok(()),
+        }
+    }
+}
+
+/// Ví mẫu cho unit test: `active` guardian đang hoạt động, trong đó `approvers` guardian có quyền phê duyệt
+#[cfg(test)]
+pub(crate) fn test_wallet(threshold: u8, active: u8, approvers: u8, owners: u8) -> MultiSigWallet {
+    MultiSigWallet {
+        version: crate::state::version::ACCOUNT_VERSION,
+        threshold,
+        guardian_count: active,
+        active_guardian_count: active,
+        owner_count: owners,
+        approver_count: approvers,
+        recovery_nonce: 0,
+        bump: 0,
+        transaction_nonce: 0,
+        last_transaction_timestamp: 0,
+        owner: Pubkey::default(),
+        credential_id: String::new(),
+        timelock: TimelockConfig::default(),
+        spending_policy: SpendingPolicy::restricted(),
+        rp_id_hash: [0; 32],
+        seed_version: SEED_VERSION_V1,
+        salt: [0; 32],
+        migrated_from: None,
+        pending_proposals: 0,
+        guardians: Vec::new(),
+        proposal_count: 0,
+        weighted_voting: false,
+        approver_weight: approvers as u16,
+        reserved: [0; WALLET_RESERVED_LEN],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::guardian::test_guardian;
+    
+    const DAY: i64 = 24 * 60 * 60;
+    
+    fn sol_limit(per_transaction_cap: u64, allowance: u64) -> SpendingLimit {
+        SpendingLimit {
+            mint: None,
+            per_transaction_cap,
+            allowance,
+            period: DAY,
+            ..SpendingLimit::default()
+        }
+    }
+    
+    fn policy(limits: Vec<SpendingLimit>) -> SpendingPolicy {
+        SpendingPolicy {
+            limits,
+            ..SpendingPolicy::restricted()
+        }
+    }
+    
+    fn tier(mint: Option<Pubkey>, min_amount: u64, delay: i64) -> TimelockTier {
+        TimelockTier { mint, min_amount, delay }
+    }
+    
+    #[test]
+    fn consume_disabled_policy_rejects_everything() {
+        let mut policy = SpendingPolicy {
+            enabled: false,
+            ..policy(vec![sol_limit(100, 100)])
+        };
+        assert_eq!(
+            policy.consume(None, 1, Pubkey::new_unique(), 0).unwrap_err(),
+            WalletError::SpendingLimitExceeded.into()
+        );
+    }
+    
+    #[test]
+    fn consume_restricted_policy_rejects_everything() {
+        let mut policy = SpendingPolicy::restricted();
+        assert_eq!(
+            policy.consume(None, 1, Pubkey::new_unique(), 0).unwrap_err(),
+            WalletError::SpendingLimitExceeded.into()
+        );
+    }
+    
+    #[test]
+    fn consume_enforces_per_transaction_cap_and_allowance() {
+        let mut policy = policy(vec![sol_limit(60, 100)]);
+        let destination = Pubkey::new_unique();
+        
+        assert!(policy.consume(None, 61, destination, 0).is_err());
+        assert!(policy.consume(None, 60, destination, 0).is_ok());
+        assert!(policy.consume(None, 40, destination, 0).is_ok());
+        assert_eq!(
+            policy.consume(None, 1, destination, 0).unwrap_err(),
+            WalletError::SpendingLimitExceeded.into()
+        );
+        assert_eq!(policy.limits[0].spent, 100);
+    }
+    
+    #[test]
+    fn consume_limits_are_per_spender() {
+        // Mỗi spender có hạn mức riêng 100, trần chung của ví là 150
+        let mut policy = policy(vec![sol_limit(100, 150)]);
+        let mut alice = Guardian {
+            spending_limits: vec![sol_limit(100, 100)],
+            ..test_guardian(Pubkey::default(), 1, false)
+        };
+        let mut bob = Guardian {
+            spending_limits: vec![sol_limit(100, 100)],
+            ..test_guardian(Pubkey::default(), 2, false)
+        };
+        let destination = Pubkey::new_unique();
+        
+        let mut spend = |spender: &mut Guardian, amount: u64| -> Result<()> {
+            spender.consume_spending(None, amount, 0)?;
+            policy.consume(None, amount, destination, 0)
+        };
+        
+        assert!(spend(&mut alice, 100).is_ok());
+        assert_eq!(spend(&mut alice, 1).unwrap_err(), WalletError::SpendingLimitExceeded.into());
+        // Hạn mức đã dùng của alice không ảnh hưởng bob, chỉ trần chung của ví
+        assert!(spend(&mut bob, 50).is_ok());
+        assert_eq!(spend(&mut bob, 1).unwrap_err(), WalletError::SpendingLimitExceeded.into());
+        assert_eq!((alice.spending_limits[0].spent, bob.spending_limits[0].spent), (100, 51));
+        
+        // Guardian chưa được cấu hình hạn mức riêng không chi trực tiếp được
+        let mut carol = test_guardian(Pubkey::default(), 3, false);
+        assert!(carol.consume_spending(None, 1, 0).is_err());
+    }
+    
+    #[test]
+    fn consume_limits_are_per_mint() {
+        let mint = Pubkey::new_unique();
+        let mut policy = policy(vec![sol_limit(100, 100)]);
+        let destination = Pubkey::new_unique();
+        
+        assert!(policy.consume(Some(mint), 1, destination, 0).is_err());
+        
+        policy.limits.push(SpendingLimit { mint: Some(mint), ..sol_limit(10, 10) });
+        assert!(policy.consume(Some(mint), 10, destination, 0).is_ok());
+        assert!(policy.consume(None, 100, destination, 0).is_ok());
+    }
+    
+    #[test]
+    fn consume_allowance_recovers_gradually() {
+        let mut policy = policy(vec![sol_limit(100, 100)]);
+        let destination = Pubkey::new_unique();
+        
+        assert!(policy.consume(None, 100, destination, DAY - 1).is_ok());
+        // Ngay sau ranh giới chu kỳ cũ, hạn mức chưa hồi lại
+        assert!(policy.consume(None, 2, destination, DAY + 1).is_err());
+        assert!(policy.consume(None, 50, destination, DAY - 1 + DAY / 2).is_ok());
+        assert!(policy.consume(None, 1, destination, DAY - 1 + DAY / 2).is_err());
+        assert_eq!(policy.limits[0].outstanding(10 * DAY), 0);
+    }
+    
+    #[test]
+    fn consume_checks_allowlist() {
+        let allowed = Pubkey::new_unique();
+        let mut policy = SpendingPolicy {
+            allowlist: vec![allowed],
+            ..policy(vec![sol_limit(100, 100)])
+        };
+        
+        assert_eq!(
+            policy.consume(None, 1, Pubkey::new_unique(), 0).unwrap_err(),
+            WalletError::DestinationNotAllowed.into()
+        );
+        assert!(policy.consume(None, 1, allowed, 0).is_ok());
+    }
+    
+    #[test]
+    fn delay_for_picks_highest_matching_tier() {
+        let mint = Pubkey::new_unique();
+        let config = TimelockConfig {
+            delay: 60,
+            tiers: vec![tier(None, 1_000, 3_600), tier(None, 10_000, DAY), tier(Some(mint), 10, 7 * DAY)],
+        };
+        
+        assert_eq!(config.delay_for(Some((None, 999))), 60);
+        assert_eq!(config.delay_for(Some((None, 1_000))), 3_600);
+        assert_eq!(config.delay_for(Some((None, 50_000))), DAY);
+        assert_eq!(config.delay_for(Some((Some(Pubkey::new_unique()), 50_000))), 60);
+        assert_eq!(config.delay_for(Some((Some(mint), 10))), 7 * DAY);
+        assert_eq!(config.delay_for(None), 7 * DAY);
+    }
+    
+    #[test]
+    fn delay_for_never_goes_below_default_delay() {
+        let config = TimelockConfig {
+            delay: DAY,
+            tiers: vec![tier(None, 1_000, 60)],
+        };
+        assert_eq!(config.delay_for(Some((None, 5_000))), DAY);
+    }
+    
+    #[test]
+    fn validate_timelock() {
+        let mint = Pubkey::new_unique();
+        let valid = TimelockConfig {
+            delay: 0,
+            tiers: vec![tier(None, 100, 60), tier(Some(mint), 10, 60), tier(None, 200, 120)],
+        };
+        assert!(valid.validate().is_ok());
+        
+        let unsorted = TimelockConfig {
+            delay: 0,
+            tiers: vec![tier(None, 200, 60), tier(Some(mint), 10, 60), tier(None, 100, 120)],
+        };
+        assert!(unsorted.validate().is_err());
+        
+        let too_long = TimelockConfig { delay: MAX_TIMELOCK_DELAY + 1, tiers: Vec::new() };
+        assert!(too_long.validate().is_err());
+        
+        let negative = TimelockConfig { delay: 0, tiers: vec![tier(None, 1, -1)] };
+        assert!(negative.validate().is_err());
+        
+        let too_many = TimelockConfig {
+            delay: 0,
+            tiers: (0..=MAX_TIMELOCK_TIERS as u64).map(|i| tier(None, i, 0)).collect(),
+        };
+        assert!(too_many.validate().is_err());
+    }
+    
+    #[test]
+    fn check_invariants_counts() {
+        assert!(test_wallet(2, 3, 2, 1).check_invariants().is_ok());
+        assert!(test_wallet(0, 3, 3, 1).check_invariants().is_err());
+        assert!(test_wallet(3, 2, 2, 1).check_invariants().is_err());
+        assert!(test_wallet(3, 3, 2, 1).check_invariants().is_err());
+        assert_eq!(
+            test_wallet(1, 3, 3, 0).check_invariants().unwrap_err(),
+            WalletError::NoActiveOwner.into()
+        );
+    }
+    
+    #[test]
+    fn check_invariants_weighted() {
+        let mut weighted = test_wallet(5, 2, 2, 1);
+        weighted.weighted_voting = true;
+        weighted.approver_weight = 5;
+        assert!(weighted.check_invariants().is_ok());
+        
+        weighted.approver_weight = 4;
+        assert!(weighted.check_invariants().is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+
+use crate::errors::WalletError;
+use crate::precompile::find_secp256r1_signature;
+
+// authenticatorData = rpIdHash (32) || flags (1) || signCount (4, big-endian) || ...
+const AUTH_DATA_MIN_LEN: usize = 37;
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+
+/// Kết quả `navigator.credentials.get()` gửi lên nguyên trạng từ trình duyệt
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct WebAuthnAssertion {
+    pub payload_version: u8,         // Cách dựng message được ký, xem `payload::signing_message`
+    pub authenticator_data: Vec<u8>,
+    pub client_data_json: Vec<u8>,
+}
+
+/// Xác thực một WebAuthn assertion:
+/// - một instruction secp256r1 bất kỳ trong transaction ký bởi `webauthn_pubkey` trên `authenticatorData || SHA256(clientDataJSON)`
+/// - rpIdHash khớp với ví, cờ UP và UV được bật, signCount tăng dần
+/// - clientDataJSON có type "webauthn.get" và challenge = base64url(SHA256(expected_message)),
+///   với expected_message lấy từ `payload::signing_message`
+///
+/// Khi hợp lệ, `sign_count` (của guardian hoặc của thiết bị đã ký) được cập nhật. Người gọi
+/// phải tăng nonce của guardian để payload đã ký không thể dùng lại.
+pub fn verify_webauthn_assertion(
+    instruction_sysvar: &AccountInfo,
+    webauthn_pubkey: &[u8; 33],
+    assertion: &WebAuthnAssertion,
+    expected_message: &[u8],
+    rp_id_hash: &[u8; 32],
+    sign_count: &mut u32,
+) -> Result<()> {
+    let authenticator_data = &assertion.authenticator_data;
+    require!(
+        authenticator_data.len() >= AUTH_DATA_MIN_LEN,
+        WalletError::InvalidAuthenticatorData
+    );
+    
+    // Message mà authenticator thực sự ký
+    let client_data_hash = hash(&assertion.client_data_json).to_bytes();
+    let mut signed_message = Vec::with_capacity(authenticator_data.len() + 32);
+    signed_message.extend_from_slice(authenticator_data);
+    signed_message.extend_from_slice(&client_data_hash);
+    
+    let secp_signature = find_secp256r1_signature(instruction_sysvar, webauthn_pubkey, &signed_message)?;
+    msg!("Chữ ký secp256r1: {}", to_hex(&secp_signature.signature));
+    
+    require!(
+        authenticator_data[0..32] == rp_id_hash[..],
+        WalletError::RpIdMismatch
+    );
+    
+    let flags = authenticator_data[32];
+    require!(
+        flags & FLAG_USER_PRESENT != 0 && flags & FLAG_USER_VERIFIED != 0,
+        WalletError::UserNotVerified
+    );
+    
+    // Authenticator không hỗ trợ bộ đếm (ví dụ passkey đồng bộ) luôn trả về 0
+    let new_sign_count = u32::from_be_bytes([
+        authenticator_data[33],
+        authenticator_data[34],
+        authenticator_data[35],
+        authenticator_data[36],
+    ]);
+    if new_sign_count != 0 || *sign_count != 0 {
+        require!(new_sign_count > *sign_count, WalletError::SignCountReplay);
+    }
+    
+    let client_data = assertion.client_data_json.as_slice();
+    require!(
+        json_string_field(client_data, "type") == Some(b"webauthn.get".as_ref()),
+        WalletError::InvalidClientData
+    );
+    
+    let expected_challenge = base64url_encode(&hash(expected_message).to_bytes());
+    let challenge = json_string_field(client_data, "challenge").ok_or(WalletError::InvalidClientData)?;
+    msg!("Expected challenge: {}", expected_challenge);
+    msg!("Received challenge: {}", String::from_utf8_lossy(challenge));
+    require!(
+        challenge == expected_challenge.as_bytes(),
+        WalletError::ChallengeMismatch
+    );
+    
+    *sign_count = new_sign_count;
+    
+    msg!("WebAuthn assertion hợp lệ, pubkey {}, signCount {}", to_hex(&webauthn_pubkey[..6]), new_sign_count);
+    
+    Ok(())
+}
+
+/// Đọc giá trị chuỗi của một trường cấp cao nhất trong clientDataJSON.
+/// Trình duyệt serialize clientDataJSON không có khoảng trắng và escape dấu `"` trong giá trị,
+/// nên chuỗi `"key":"` không thể xuất hiện bên trong một giá trị khác.
+fn json_string_field<'a>(json: &'a [u8], key: &str) -> Option<&'a [u8]> {
+    let pattern = format!("\"{}\":\"", key);
+    let pattern = pattern.as_bytes();
+    let start = json
+        .windows(pattern.len())
+        .position(|window| window == pattern)?
+        + pattern.len();
+    let len = json[start..].iter().position(|byte| *byte == b'"')?;
+    Some(&json[start..start + len])
+}
+
+fn base64url_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    
+    let mut result = String::with_capacity((bytes.len() * 4).div_ceil(3));
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let n = (b0 << 16) | (b1 << 8) | b2;
+        
+        // Không padding: 1 byte -> 2 ký tự, 2 byte -> 3 ký tự, 3 byte -> 4 ký tự
+        for i in 0..=chunk.len() {
+            result.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+        }
+    }
+    result
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let hex = format!("{:02x}", byte);
+        result.push_str(&hex);
+    }
+    result
+}
@@ -0,0 +1,243 @@
+/**
+ * So sánh compute unit của approve_proposal và execute_proposal (ví thường, mỗi guardian một PDA)
+ * với approve_large_proposal và execute_large_proposal (ví zero-copy, bảng guardian trong tài khoản ví).
+ * Đề xuất được đo ở cả hai layout là chuyển 1 lamport, ngưỡng 1.
+ *
+ * Cần build với layout thử nghiệm:
+ *   anchor build -- --features localnet,experimental-large-wallet && anchor test
+ *
+ * Guardian phê duyệt dùng khóa Signer nên không cần passkey; CU của xác thực passkey
+ * (secp256r1) như nhau ở cả hai layout nên không ảnh hưởng tới phép so sánh.
+ */
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import {
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_CLOCK_PUBKEY,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  Transaction,
+} from "@solana/web3.js";
+import { createHash } from "crypto";
+
+const PERMISSION_ALL = 0b1111;
+const PERMISSION_PROPOSE_APPROVE = (1 << 2) | (1 << 3);
+const MAX_GUARDIANS = 8;
+const MAX_LARGE_GUARDIANS = 64;
+const RP_ID = "moonwallet.app";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+const program = anchor.workspace.moonWalletProgram as Program;
+const creator = provider.wallet.publicKey;
+
+const u64Le = (value: number) => new BN(value).toArrayLike(Buffer, "le", 8);
+const now = () => new BN(Math.floor(Date.now() / 1000));
+const signerProof = { signer: {} };
+
+const walletSeed = (credentialId: string, salt: Buffer) =>
+  createHash("sha256").update(credentialId).update(creator.toBuffer()).update(salt).digest();
+
+const pda = (seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+// Guardian ký bằng chính keypair của mình, truyền qua remaining_accounts
+const asSigner = (guardian: Keypair) => [{ pubkey: guardian.publicKey, isSigner: true, isWritable: false }];
+
+const computeUnits = async (signature: string): Promise<number> => {
+  const tx = await provider.connection.getTransaction(signature, {
+    commitment: "confirmed",
+    maxSupportedTransactionVersion: 0,
+  });
+  return tx?.meta?.computeUnitsConsumed ?? NaN;
+};
+
+const ownerAuth = { passkey: [Array.from(Buffer.alloc(33, 2))] };
+
+type BenchResult = { approveCu: number; executeCu: number };
+
+// Nạp SOL cho ví để lệnh thực thi chuyển được mà ví vẫn đủ rent
+const fund = (wallet: PublicKey) =>
+  provider.sendAndConfirm(
+    new Transaction().add(SystemProgram.transfer({ fromPubkey: creator, toPubkey: wallet, lamports: 10_000_000 }))
+  );
+
+/** Ví thường với `count` guardian, guardian 1 là owner; trả về CU của một lần phê duyệt và một lần thực thi */
+async function benchStandard(count: number): Promise<BenchResult> {
+  const credentialId = `bench-standard-${count}`;
+  const salt = Keypair.generate().publicKey.toBuffer();
+  const multisig = pda([Buffer.from("multisig"), walletSeed(credentialId, salt)]);
+  const guardianPda = (id: number) => pda([Buffer.from("guardian"), multisig.toBuffer(), u64Le(id)]);
+  const guardians = Array.from({ length: count + 1 }, () => Keypair.generate());
+
+  // Mỗi lần thêm guardian ví phải thỏa bất biến, nên ngưỡng khởi tạo là 1
+  await program.methods
+    .initializeMultisig(1, credentialId, RP_ID, Array.from(salt))
+    .accountsStrict({ feePayer: creator, multisig, systemProgram: SystemProgram.programId })
+    .rpc();
+
+  for (let id = 1; id <= count; id++) {
+    const isOwner = id === 1;
+    await program.methods
+      .addGuardian(
+        new BN(id),
+        `guardian ${id}`,
+        Array.from(Buffer.alloc(32)),
+        isOwner,
+        isOwner ? ownerAuth : { signer: [guardians[id].publicKey] },
+        isOwner ? PERMISSION_ALL : PERMISSION_PROPOSE_APPROVE,
+        null
+      )
+      .accountsStrict({
+        multisig,
+        guardian: guardianPda(id),
+        guardianPubkey: guardians[id].publicKey,
+        payer: creator,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  const proposal = pda([Buffer.from("proposal"), multisig.toBuffer(), u64Le(0)]);
+  await program.methods
+    .createProposal(
+      "bench",
+      new BN(2),
+      "transfer",
+      { amount: new BN(1), destination: creator, tokenMint: null },
+      [],
+      [],
+      null,
+      null,
+      now(),
+      signerProof
+    )
+    .accountsStrict({
+      multisig,
+      proposal,
+      proposerGuardian: guardianPda(2),
+      payer: creator,
+      instructionSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+      clock: SYSVAR_CLOCK_PUBKEY,
+      systemProgram: SystemProgram.programId,
+    })
+    .remainingAccounts(asSigner(guardians[2]))
+    .signers([guardians[2]])
+    .rpc();
+
+  const approver = count;
+  const approveSignature = await program.methods
+    .approveProposal(new BN(0), new BN(approver), now(), signerProof, false)
+    .accountsStrict({
+      multisig,
+      proposal,
+      signature: pda([Buffer.from("signature"), proposal.toBuffer(), u64Le(approver)]),
+      guardian: guardianPda(approver),
+      payer: creator,
+      instructionSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+      clock: SYSVAR_CLOCK_PUBKEY,
+      systemProgram: SystemProgram.programId,
+    })
+    .remainingAccounts(asSigner(guardians[approver]))
+    .signers([guardians[approver]])
+    .rpc({ commitment: "confirmed" });
+
+  await fund(multisig);
+  const executeSignature = await program.methods
+    .executeProposal(new BN(0))
+    .accountsStrict({
+      multisig,
+      proposal,
+      payer: creator,
+      destination: creator,
+      vaultTokenAccount: null,
+      destinationTokenAccount: null,
+      tokenMint: null,
+      tokenProgram: null,
+      clock: SYSVAR_CLOCK_PUBKEY,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc({ commitment: "confirmed" });
+
+  return { approveCu: await computeUnits(approveSignature), executeCu: await computeUnits(executeSignature) };
+}
+
+/** Ví zero-copy với `count` guardian; guardian phê duyệt nằm ở ô cuối của bảng */
+async function benchLarge(count: number): Promise<BenchResult> {
+  const credentialId = `bench-large-${count}`;
+  const salt = Keypair.generate().publicKey.toBuffer();
+  const wallet = pda([Buffer.from("large_multisig"), walletSeed(credentialId, salt)]);
+  const guardians = Array.from({ length: count + 1 }, () => Keypair.generate());
+
+  await program.methods
+    .initializeLargeWallet(1, credentialId, RP_ID, Array.from(salt))
+    .accountsStrict({ feePayer: creator, wallet, systemProgram: SystemProgram.programId })
+    .rpc();
+
+  for (let id = 1; id <= count; id++) {
+    const isOwner = id === 1;
+    await program.methods
+      .addLargeGuardian(
+        new BN(id),
+        isOwner,
+        isOwner ? ownerAuth : { signer: [guardians[id].publicKey] },
+        isOwner ? PERMISSION_ALL : PERMISSION_PROPOSE_APPROVE
+      )
+      .accountsStrict({ wallet, owner: creator })
+      .rpc();
+  }
+
+  const proposal = pda([Buffer.from("large_proposal"), wallet.toBuffer(), u64Le(0)]);
+  await program.methods
+    .createLargeProposal(new BN(2), new BN(1), creator, now(), signerProof)
+    .accountsStrict({
+      wallet,
+      proposal,
+      payer: creator,
+      instructionSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+      clock: SYSVAR_CLOCK_PUBKEY,
+      systemProgram: SystemProgram.programId,
+    })
+    .remainingAccounts(asSigner(guardians[2]))
+    .signers([guardians[2]])
+    .rpc();
+
+  const approver = count;
+  const approveSignature = await program.methods
+    .approveLargeProposal(new BN(0), new BN(approver), now(), signerProof)
+    .accountsStrict({
+      wallet,
+      proposal,
+      payer: creator,
+      instructionSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+      clock: SYSVAR_CLOCK_PUBKEY,
+    })
+    .remainingAccounts(asSigner(guardians[approver]))
+    .signers([guardians[approver]])
+    .rpc({ commitment: "confirmed" });
+
+  await fund(wallet);
+  const executeSignature = await program.methods
+    .executeLargeProposal(new BN(0))
+    .accountsStrict({
+      wallet,
+      proposal,
+      destination: creator,
+      payer: creator,
+      clock: SYSVAR_CLOCK_PUBKEY,
+    })
+    .rpc({ commitment: "confirmed" });
+
+  return { approveCu: await computeUnits(approveSignature), executeCu: await computeUnits(executeSignature) };
+}
+
+describe("large wallet compute units", () => {
+  it("logs approve and execute CU for both layouts", async () => {
+    const results = [
+      { layout: "standard", guardians: MAX_GUARDIANS, ...(await benchStandard(MAX_GUARDIANS)) },
+      { layout: "zero-copy", guardians: MAX_GUARDIANS, ...(await benchLarge(MAX_GUARDIANS)) },
+      { layout: "zero-copy", guardians: MAX_LARGE_GUARDIANS, ...(await benchLarge(MAX_LARGE_GUARDIANS)) },
+    ];
+    console.table(results);
+  });
+});
@@ -13,6 +13,85 @@ export type MoonWalletProgram = {
     "description": "Created with Anchor"
   },
   "instructions": [
+    {
+      "name": "addDevice",
+      "discriminator": [
+        21,
+        27,
+        66,
+        42,
+        18,
+        30,
+        14,
+        18
+      ],
+      "accounts": [
+        {
+          "name": "multisig"
+        },
+        {
+          "name": "guardian",
+          "writable": true
+        },
+        {
+          "name": "signerDevice",
+          "docs": [
+            "Thiết bị đã đăng ký ký thao tác này. Bỏ trống: passkey chính của owner ký."
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "device",
+          "writable": true
+        },
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "instructionSysvar"
+        },
+        {
+          "name": "clock"
+        },
+        {
+          "name": "systemProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "credentialId",
+          "type": "string"
+        },
+        {
+          "name": "pubkey",
+          "type": {
+            "array": [
+              "u8",
+              33
+            ]
+          }
+        },
+        {
+          "name": "label",
+          "type": "string"
+        },
+        {
+          "name": "timestamp",
+          "type": "i64"
+        },
+        {
+          "name": "assertion",
+          "type": {
+            "defined": {
+              "name": "webAuthnAssertion"
+            }
+          }
+        }
+      ]
+    },
     {
       "name": "addGuardian",
       "discriminator": [
@@ -69,15 +148,22 @@ export type MoonWalletProgram = {
           "type": "bool"
         },
         {
-          "name": "webauthnPubkey",
+          "name": "auth",
           "type": {
-            "option": {
-              "array": [
-                "u8",
-                33
-              ]
+            "defined": {
+              "name": "guardianAuth"
             }
           }
+        },
+        {
+          "name": "permissions",
+          "type": "u8"
+        },
+        {
+          "name": "weight",
+          "type": {
+            "option": "u8"
+          }
         }
       ]
     },
@@ -107,7 +193,8 @@ export type MoonWalletProgram = {
           "writable": true
         },
         {
-          "name": "guardian"
+          "name": "guardian",
+          "writable": true
         },
         {
           "name": "payer",
@@ -138,40 +225,55 @@ export type MoonWalletProgram = {
           "type": "i64"
         },
         {
-          "name": "message",
-          "type": "bytes"
+          "name": "proof",
+          "type": {
+            "defined": {
+              "name": "guardianProof"
+            }
+          }
+        },
+        {
+          "name": "executeIfReady",
+          "type": "bool"
         }
       ]
     },
     {
-      "name": "createProposal",
+      "name": "approveRecovery",
       "discriminator": [
-        132,
-        116,
-        68,
-        174,
-        216,
-        160,
-        198,
-        22
+        148,
+        96,
+        41,
+        38,
+        108,
+        189,
+        129,
+        214
       ],
       "accounts": [
         {
-          "name": "multisig",
+          "name": "multisig"
+        },
+        {
+          "name": "recoveryRequest",
           "writable": true
         },
         {
-          "name": "proposal",
+          "name": "approval",
           "writable": true
         },
         {
-          "name": "proposerGuardian"
+          "name": "guardian",
+          "writable": true
         },
         {
           "name": "payer",
           "writable": true,
           "signer": true
         },
+        {
+          "name": "instructionSysvar"
+        },
         {
           "name": "clock"
         },
@@ -181,42 +283,34 @@ export type MoonWalletProgram = {
       ],
       "args": [
         {
-          "name": "proposalId",
-          "type": "u64"
-        },
-        {
-          "name": "description",
-          "type": "string"
-        },
-        {
-          "name": "proposerGuardianId",
+          "name": "guardianId",
           "type": "u64"
         },
         {
-          "name": "action",
-          "type": "string"
+          "name": "timestamp",
+          "type": "i64"
         },
         {
-          "name": "params",
+          "name": "proof",
           "type": {
             "defined": {
-              "name": "actionParams"
+              "name": "guardianProof"
             }
           }
         }
       ]
     },
     {
-      "name": "executeProposal",
+      "name": "batchApprove",
       "discriminator": [
-        186,
-        60,
+        225,
+        132,
+        148,
+        68,
+        137,
         116,
-        133,
-        108,
-        128,
-        111,
-        28
+        171,
+        149
       ],
       "accounts": [
         {
@@ -234,7 +328,29 @@ export type MoonWalletProgram = {
         },
         {
           "name": "destination",
-          "writable": true
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "vaultTokenAccount",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "destinationTokenAccount",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenMint",
+          "optional": true
+        },
+        {
+          "name": "tokenProgram",
+          "optional": true
+        },
+        {
+          "name": "instructionSysvar"
         },
         {
           "name": "clock"
@@ -247,20 +363,34 @@ export type MoonWalletProgram = {
         {
           "name": "proposalId",
           "type": "u64"
+        },
+        {
+          "name": "approvals",
+          "type": {
+            "vec": {
+              "defined": {
+                "name": "guardianApproval"
+              }
+            }
+          }
+        },
+        {
+          "name": "execute",
+          "type": "bool"
         }
       ]
     },
     {
-      "name": "initializeMultisig",
+      "name": "cancelProposal",
       "discriminator": [
-        220,
-        130,
-        117,
-        21,
-        27,
-        227,
-        78,
-        213
+        106,
+        74,
+        128,
+        146,
+        19,
+        65,
+        39,
+        23
       ],
       "accounts": [
         {
@@ -268,36 +398,32 @@ export type MoonWalletProgram = {
           "writable": true
         },
         {
-          "name": "feePayer",
-          "writable": true,
-          "signer": true
+          "name": "proposal",
+          "writable": true
         },
         {
-          "name": "systemProgram"
+          "name": "proposer",
+          "signer": true
         }
       ],
       "args": [
         {
-          "name": "threshold",
-          "type": "u8"
-        },
-        {
-          "name": "credentialId",
-          "type": "string"
+          "name": "proposalId",
+          "type": "u64"
         }
       ]
     },
     {
-      "name": "recoverAccessByGuardian",
+      "name": "cancelRecovery",
       "discriminator": [
-        210,
-        31,
-        244,
-        215,
+        176,
+        23,
+        203,
+        37,
         121,
-        93,
-        165,
-        99
+        251,
+        227,
+        83
       ],
       "accounts": [
         {
@@ -305,63 +431,61 @@ export type MoonWalletProgram = {
           "writable": true
         },
         {
-          "name": "oldGuardian",
+          "name": "recoveryRequest",
           "writable": true
         },
         {
-          "name": "oldGuardianPubkey"
+          "name": "ownerGuardian",
+          "docs": [
+            "Owner hiện tại, là guardian đang bị khôi phục"
+          ],
+          "writable": true
         },
         {
-          "name": "newGuardian",
-          "writable": true
+          "name": "device",
+          "docs": [
+            "Thiết bị passkey bổ sung của owner đã ký. Bỏ trống: passkey chính của owner ký."
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "payer",
+          "signer": true
         },
         {
-          "name": "newGuardianPubkey"
+          "name": "instructionSysvar"
         },
         {
-          "name": "systemProgram"
+          "name": "clock"
         }
       ],
       "args": [
         {
-          "name": "oldGuardianId",
-          "type": "u64"
-        },
-        {
-          "name": "newGuardianId",
-          "type": "u64"
-        },
-        {
-          "name": "recoveryHashIntermediate",
-          "type": {
-            "array": [
-              "u8",
-              32
-            ]
-          }
+          "name": "timestamp",
+          "type": "i64"
         },
         {
-          "name": "newWebauthnPubkey",
+          "name": "assertion",
           "type": {
-            "array": [
-              "u8",
-              33
-            ]
+            "defined": {
+              "name": "webAuthnAssertion"
+            }
           }
         }
       ]
     },
     {
-      "name": "rejectProposal",
+      "name": "cancelRecoveryByGuardian",
       "discriminator": [
-        114,
-        162,
-        164,
-        82,
-        191,
-        11,
-        102,
-        25
+        86,
+        210,
+        50,
+        248,
+        153,
+        250,
+        40,
+        89
       ],
       "accounts": [
         {
@@ -369,15 +493,15 @@ export type MoonWalletProgram = {
           "writable": true
         },
         {
-          "name": "proposal",
+          "name": "recoveryRequest",
           "writable": true
         },
         {
-          "name": "guardian"
+          "name": "guardian",
+          "writable": true
         },
         {
           "name": "payer",
-          "writable": true,
           "signer": true
         },
         {
@@ -388,10 +512,6 @@ export type MoonWalletProgram = {
         }
       ],
       "args": [
-        {
-          "name": "proposalId",
-          "type": "u64"
-        },
         {
           "name": "guardianId",
           "type": "u64"
@@ -401,120 +521,100 @@ export type MoonWalletProgram = {
           "type": "i64"
         },
         {
-          "name": "message",
-          "type": "bytes"
+          "name": "proof",
+          "type": {
+            "defined": {
+              "name": "guardianProof"
+            }
+          }
         }
       ]
     },
     {
-      "name": "removeGuardian",
+      "name": "closeProposal",
       "discriminator": [
-        72,
-        117,
-        160,
-        244,
-        155,
-        185,
-        71,
-        18
+        213,
+        178,
+        139,
+        19,
+        50,
+        191,
+        82,
+        245
       ],
       "accounts": [
         {
-          "name": "multisig",
-          "writable": true
+          "name": "multisig"
         },
         {
-          "name": "guardian",
+          "name": "proposal",
           "writable": true
         },
         {
-          "name": "guardianPubkey"
-        },
-        {
-          "name": "ownerGuardian"
+          "name": "rentReceiver",
+          "writable": true
         },
         {
-          "name": "owner",
-          "writable": true,
+          "name": "payer",
           "signer": true
         },
         {
-          "name": "systemProgram"
+          "name": "clock"
         }
       ],
       "args": [
         {
-          "name": "guardianId",
-          "type": "u64"
-        },
-        {
-          "name": "ownerGuardianId",
+          "name": "proposalId",
           "type": "u64"
         }
       ]
     },
     {
-      "name": "updateGuardianStatus",
+      "name": "closeRecoveryApproval",
       "discriminator": [
-        17,
-        169,
-        132,
-        234,
-        235,
-        231,
-        211,
-        79
+        8,
+        195,
+        149,
+        192,
+        250,
+        120,
+        151,
+        49
       ],
       "accounts": [
         {
-          "name": "multisig",
-          "writable": true
-        },
-        {
-          "name": "guardian",
-          "writable": true
+          "name": "multisig"
         },
         {
-          "name": "guardianPubkey"
+          "name": "recoveryRequest"
         },
         {
-          "name": "ownerGuardian",
-          "docs": [
-            "Tài khoản guardian của người gọi, phải là owner"
-          ]
+          "name": "approval",
+          "writable": true
         },
         {
-          "name": "owner",
-          "writable": true,
-          "signer": true
+          "name": "rentReceiver",
+          "writable": true
         }
       ],
       "args": [
         {
           "name": "guardianId",
           "type": "u64"
-        },
-        {
-          "name": "ownerGuardianId",
-          "type": "u64"
-        },
-        {
-          "name": "isActive",
-          "type": "bool"
         }
       ]
     },
     {
-      "name": "verifyAndExecute",
+      "name": "closeRevokedDevice",
       "discriminator": [
-        37,
-        165,
-        237,
-        189,
-        225,
-        188,
-        58,
-        41
+        78,
+        102,
+        108,
+        62,
+        18,
+        28,
+        199,
+        200
       ],
       "accounts": [
         {
@@ -522,16 +622,40 @@ export type MoonWalletProgram = {
           "writable": true
         },
         {
-          "name": "guardian"
+          "name": "guardian",
+          "writable": true
         },
         {
-          "name": "clock"
+          "name": "device",
+          "writable": true
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "createProposal",
+      "discriminator": [
+        132,
+        116,
+        68,
+        174,
+        216,
+        160,
+        198,
+        22
+      ],
+      "accounts": [
+        {
+          "name": "multisig",
+          "writable": true
         },
         {
-          "name": "instructionSysvar"
+          "name": "proposal",
+          "writable": true
         },
         {
-          "name": "systemProgram"
+          "name": "proposerGuardian",
+          "writable": true
         },
         {
           "name": "payer",
@@ -539,11 +663,24 @@ export type MoonWalletProgram = {
           "signer": true
         },
         {
-          "name": "destination",
-          "writable": true
+          "name": "instructionSysvar"
+        },
+        {
+          "name": "clock"
+        },
+        {
+          "name": "systemProgram"
         }
       ],
       "args": [
+        {
+          "name": "description",
+          "type": "string"
+        },
+        {
+          "name": "proposerGuardianId",
+          "type": "u64"
+        },
         {
           "name": "action",
           "type": "string"
@@ -557,83 +694,729 @@ export type MoonWalletProgram = {
           }
         },
         {
-          "name": "nonce",
-          "type": "u64"
+          "name": "instructions",
+          "type": {
+            "vec": {
+              "defined": {
+                "name": "proposalInstruction"
+              }
+            }
+          }
+        },
+        {
+          "name": "actions",
+          "type": {
+            "vec": {
+              "defined": {
+                "name": "proposalAction"
+              }
+            }
+          }
+        },
+        {
+          "name": "expiresAt",
+          "type": {
+            "option": "i64"
+          }
+        },
+        {
+          "name": "configAction",
+          "type": {
+            "option": {
+              "defined": {
+                "name": "configAction"
+              }
+            }
+          }
         },
         {
           "name": "timestamp",
           "type": "i64"
         },
         {
-          "name": "message",
-          "type": "bytes"
+          "name": "proof",
+          "type": {
+            "defined": {
+              "name": "guardianProof"
+            }
+          }
         }
       ]
-    }
-  ],
-  "accounts": [
-    {
-      "name": "guardian",
-      "discriminator": [
-        57,
-        234,
-        122,
-        214,
-        12,
-        246,
-        9,
-        45
-      ]
     },
     {
-      "name": "multiSigWallet",
+      "name": "executeProposal",
       "discriminator": [
-        93,
-        17,
-        107,
+        186,
+        60,
+        116,
         133,
-        10,
-        77,
-        189,
-        238
-      ]
-    },
-    {
-      "name": "proposalSignature",
-      "discriminator": [
-        206,
-        136,
-        120,
-        195,
-        80,
-        13,
-        39,
-        74
+        108,
+        128,
+        111,
+        28
+      ],
+      "accounts": [
+        {
+          "name": "multisig",
+          "writable": true
+        },
+        {
+          "name": "proposal",
+          "writable": true
+        },
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "destination",
+          "writable": true
+        },
+        {
+          "name": "vaultTokenAccount",
+          "docs": [
+            "Tài khoản token (ATA) của ví multisig, chỉ dùng cho token_transfer"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "destinationTokenAccount",
+          "docs": [
+            "Tài khoản token nhận, phải thuộc sở hữu của địa chỉ đích"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenMint",
+          "optional": true
+        },
+        {
+          "name": "tokenProgram",
+          "optional": true
+        },
+        {
+          "name": "clock"
+        },
+        {
+          "name": "systemProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "proposalId",
+          "type": "u64"
+        }
       ]
     },
     {
-      "name": "transactionProposal",
+      "name": "expireProposal",
       "discriminator": [
-        39,
-        205,
+        21,
+        237,
+        43,
+        176,
+        1,
         202,
-        42,
-        47,
-        200,
-        144,
-        95
+        146,
+        144
+      ],
+      "accounts": [
+        {
+          "name": "multisig",
+          "writable": true
+        },
+        {
+          "name": "proposal",
+          "writable": true
+        },
+        {
+          "name": "clock"
+        }
+      ],
+      "args": [
+        {
+          "name": "proposalId",
+          "type": "u64"
+        }
       ]
-    }
-  ],
-  "errors": [
-    {
-      "code": 6000,
-      "name": "invalidOperation",
-      "msg": "Không có quyền hoặc dữ liệu không hợp lệ"
     },
     {
-      "code": 6001,
-      "name": "limitExceeded",
+      "name": "finalizeRecovery",
+      "discriminator": [
+        180,
+        175,
+        56,
+        254,
+        138,
+        101,
+        151,
+        219
+      ],
+      "accounts": [
+        {
+          "name": "multisig",
+          "writable": true
+        },
+        {
+          "name": "recoveryRequest",
+          "writable": true
+        },
+        {
+          "name": "oldGuardian",
+          "writable": true
+        },
+        {
+          "name": "newGuardian",
+          "writable": true
+        },
+        {
+          "name": "payer",
+          "signer": true
+        },
+        {
+          "name": "clock"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "initializeMultisig",
+      "discriminator": [
+        220,
+        130,
+        117,
+        21,
+        27,
+        227,
+        78,
+        213
+      ],
+      "accounts": [
+        {
+          "name": "feePayer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "multisig",
+          "writable": true
+        },
+        {
+          "name": "systemProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "threshold",
+          "type": "u8"
+        },
+        {
+          "name": "credentialId",
+          "type": "string"
+        },
+        {
+          "name": "rpId",
+          "type": "string"
+        },
+        {
+          "name": "salt",
+          "type": {
+            "array": [
+              "u8",
+              32
+            ]
+          }
+        }
+      ]
+    },
+    {
+      "name": "initiateRecovery",
+      "discriminator": [
+        132,
+        148,
+        60,
+        74,
+        49,
+        178,
+        235,
+        187
+      ],
+      "accounts": [
+        {
+          "name": "multisig"
+        },
+        {
+          "name": "recoveryRequest",
+          "writable": true
+        },
+        {
+          "name": "oldGuardian"
+        },
+        {
+          "name": "newGuardian"
+        },
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "clock"
+        },
+        {
+          "name": "systemProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "oldGuardianId",
+          "type": "u64"
+        },
+        {
+          "name": "newGuardianId",
+          "type": "u64"
+        },
+        {
+          "name": "recoveryHashIntermediate",
+          "type": {
+            "array": [
+              "u8",
+              32
+            ]
+          }
+        },
+        {
+          "name": "newWebauthnPubkey",
+          "type": {
+            "array": [
+              "u8",
+              33
+            ]
+          }
+        },
+        {
+          "name": "newRecoveryHash",
+          "type": {
+            "array": [
+              "u8",
+              32
+            ]
+          }
+        }
+      ]
+    },
+    {
+      "name": "migrateAccount",
+      "discriminator": [
+        177,
+        228,
+        60,
+        125,
+        13,
+        116,
+        44,
+        84
+      ],
+      "accounts": [
+        {
+          "name": "target",
+          "writable": true
+        },
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "walletParams",
+          "type": {
+            "option": {
+              "defined": {
+                "name": "walletMigrationParams"
+              }
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "rejectProposal",
+      "discriminator": [
+        114,
+        162,
+        164,
+        82,
+        191,
+        11,
+        102,
+        25
+      ],
+      "accounts": [
+        {
+          "name": "multisig",
+          "writable": true
+        },
+        {
+          "name": "proposal",
+          "writable": true
+        },
+        {
+          "name": "signature",
+          "writable": true
+        },
+        {
+          "name": "guardian",
+          "writable": true
+        },
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "instructionSysvar"
+        },
+        {
+          "name": "clock"
+        },
+        {
+          "name": "systemProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "proposalId",
+          "type": "u64"
+        },
+        {
+          "name": "guardianId",
+          "type": "u64"
+        },
+        {
+          "name": "timestamp",
+          "type": "i64"
+        },
+        {
+          "name": "proof",
+          "type": {
+            "defined": {
+              "name": "guardianProof"
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "removeDevice",
+      "discriminator": [
+        42,
+        19,
+        175,
+        5,
+        67,
+        100,
+        238,
+        14
+      ],
+      "accounts": [
+        {
+          "name": "multisig"
+        },
+        {
+          "name": "guardian",
+          "writable": true
+        },
+        {
+          "name": "signerDevice",
+          "docs": [
+            "Thiết bị đã đăng ký ký thao tác này, phải khác thiết bị bị gỡ.",
+            "Bỏ trống: passkey chính của owner ký."
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "device",
+          "writable": true
+        },
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "instructionSysvar"
+        },
+        {
+          "name": "clock"
+        }
+      ],
+      "args": [
+        {
+          "name": "timestamp",
+          "type": "i64"
+        },
+        {
+          "name": "assertion",
+          "type": {
+            "defined": {
+              "name": "webAuthnAssertion"
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "sweepLegacyTokens",
+      "discriminator": [
+        110,
+        160,
+        244,
+        151,
+        30,
+        74,
+        152,
+        167
+      ],
+      "accounts": [
+        {
+          "name": "multisig",
+          "writable": true
+        },
+        {
+          "name": "legacyWallet"
+        },
+        {
+          "name": "legacyTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "vaultTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "tokenMint"
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "verifyAndExecute",
+      "discriminator": [
+        37,
+        165,
+        237,
+        189,
+        225,
+        188,
+        58,
+        41
+      ],
+      "accounts": [
+        {
+          "name": "multisig",
+          "writable": true
+        },
+        {
+          "name": "guardian",
+          "writable": true
+        },
+        {
+          "name": "device",
+          "docs": [
+            "Thiết bị passkey bổ sung đã ký giao dịch. Bỏ trống: passkey chính của owner ký."
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "clock"
+        },
+        {
+          "name": "instructionSysvar"
+        },
+        {
+          "name": "systemProgram"
+        },
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "destination",
+          "writable": true
+        },
+        {
+          "name": "vaultTokenAccount",
+          "docs": [
+            "Tài khoản token (ATA) của ví multisig, chỉ dùng cho token_transfer"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "destinationTokenAccount",
+          "docs": [
+            "Tài khoản token nhận, phải thuộc sở hữu của địa chỉ đích"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenMint",
+          "optional": true
+        },
+        {
+          "name": "tokenProgram",
+          "optional": true
+        }
+      ],
+      "args": [
+        {
+          "name": "action",
+          "type": "string"
+        },
+        {
+          "name": "params",
+          "type": {
+            "defined": {
+              "name": "actionParams"
+            }
+          }
+        },
+        {
+          "name": "nonce",
+          "type": "u64"
+        },
+        {
+          "name": "timestamp",
+          "type": "i64"
+        },
+        {
+          "name": "assertion",
+          "type": {
+            "defined": {
+              "name": "webAuthnAssertion"
+            }
+          }
+        }
+      ]
+    }
+  ],
+  "accounts": [
+    {
+      "name": "guardian",
+      "discriminator": [
+        57,
+        234,
+        122,
+        214,
+        12,
+        246,
+        9,
+        45
+      ]
+    },
+    {
+      "name": "multiSigWallet",
+      "discriminator": [
+        93,
+        17,
+        107,
+        133,
+        10,
+        77,
+        189,
+        238
+      ]
+    },
+    {
+      "name": "ownerDevice",
+      "discriminator": [
+        184,
+        6,
+        176,
+        158,
+        142,
+        174,
+        20,
+        150
+      ]
+    },
+    {
+      "name": "proposalSignature",
+      "discriminator": [
+        206,
+        136,
+        120,
+        195,
+        80,
+        13,
+        39,
+        74
+      ]
+    },
+    {
+      "name": "recoveryApproval",
+      "discriminator": [
+        152,
+        70,
+        190,
+        159,
+        36,
+        158,
+        96,
+        21
+      ]
+    },
+    {
+      "name": "recoveryRequest",
+      "discriminator": [
+        143,
+        116,
+        126,
+        64,
+        175,
+        138,
+        150,
+        111
+      ]
+    },
+    {
+      "name": "transactionProposal",
+      "discriminator": [
+        39,
+        205,
+        202,
+        42,
+        47,
+        200,
+        144,
+        95
+      ]
+    }
+  ],
+  "errors": [
+    {
+      "code": 6000,
+      "name": "invalidOperation",
+      "msg": "Không có quyền hoặc dữ liệu không hợp lệ"
+    },
+    {
+      "code": 6001,
+      "name": "limitExceeded",
       "msg": "Giới hạn đã đạt tối đa"
     },
     {
@@ -692,126 +1475,1192 @@ export type MoonWalletProgram = {
       "msg": "Timestamp quá cũ"
     },
     {
-      "code": 6013,
-      "name": "expiredTimestamp",
-      "msg": "Timestamp đã hết hạn"
+      "code": 6013,
+      "name": "expiredTimestamp",
+      "msg": "Timestamp đã hết hạn"
+    },
+    {
+      "code": 6014,
+      "name": "instructionMissing",
+      "msg": "Instruction xác thực chữ ký bị thiếu"
+    },
+    {
+      "code": 6015,
+      "name": "invalidSignatureVerification",
+      "msg": "Xác thực chữ ký không hợp lệ"
+    },
+    {
+      "code": 6016,
+      "name": "publicKeyMismatch",
+      "msg": "Public key không khớp với wallet"
+    },
+    {
+      "code": 6017,
+      "name": "messageMismatch",
+      "msg": "Message không khớp"
+    },
+    {
+      "code": 6018,
+      "name": "invalidInstructionData",
+      "msg": "Dữ liệu instruction không hợp lệ"
+    },
+    {
+      "code": 6019,
+      "name": "invalidSignatureCount",
+      "msg": "Số lượng chữ ký không hợp lệ"
+    },
+    {
+      "code": 6020,
+      "name": "unsupportedAction",
+      "msg": "Hành động không được hỗ trợ"
+    },
+    {
+      "code": 6021,
+      "name": "invalidGuardian",
+      "msg": "Guardian không hợp lệ hoặc không được tìm thấy"
+    },
+    {
+      "code": 6022,
+      "name": "inactiveGuardian",
+      "msg": "Guardian đang không hoạt động"
+    },
+    {
+      "code": 6023,
+      "name": "arithmeticOverflow",
+      "msg": "Lỗi tràn số trong phép tính"
+    },
+    {
+      "code": 6024,
+      "name": "insufficientFunds",
+      "msg": "Không đủ SOL trong tài khoản"
+    },
+    {
+      "code": 6025,
+      "name": "multisigMismatch",
+      "msg": "Địa chỉ ví multisig không khớp với đề xuất"
+    },
+    {
+      "code": 6026,
+      "name": "invalidOwner",
+      "msg": "Chủ sở hữu tài khoản không hợp lệ"
+    },
+    {
+      "code": 6027,
+      "name": "tokenAccountMissing",
+      "msg": "Thiếu tài khoản token cho giao dịch"
+    },
+    {
+      "code": 6028,
+      "name": "invalidTokenAccount",
+      "msg": "Tài khoản token không hợp lệ"
+    },
+    {
+      "code": 6029,
+      "name": "tokenMintMismatch",
+      "msg": "Token mint không khớp"
+    },
+    {
+      "code": 6030,
+      "name": "cpiAccountMissing",
+      "msg": "Thiếu tài khoản cho instruction CPI"
+    },
+    {
+      "code": 6031,
+      "name": "invalidCpiProgram",
+      "msg": "Không được phép gọi CPI tới program này"
+    },
+    {
+      "code": 6032,
+      "name": "invalidBatchStep",
+      "msg": "Bước trong batch không hợp lệ"
+    },
+    {
+      "code": 6033,
+      "name": "batchStep0Failed",
+      "msg": "Bước 0 của batch thực thi thất bại"
+    },
+    {
+      "code": 6034,
+      "name": "batchStep1Failed",
+      "msg": "Bước 1 của batch thực thi thất bại"
+    },
+    {
+      "code": 6035,
+      "name": "batchStep2Failed",
+      "msg": "Bước 2 của batch thực thi thất bại"
+    },
+    {
+      "code": 6036,
+      "name": "batchStep3Failed",
+      "msg": "Bước 3 của batch thực thi thất bại"
+    },
+    {
+      "code": 6037,
+      "name": "batchStep4Failed",
+      "msg": "Bước 4 của batch thực thi thất bại"
+    },
+    {
+      "code": 6038,
+      "name": "batchStep5Failed",
+      "msg": "Bước 5 của batch thực thi thất bại"
+    },
+    {
+      "code": 6039,
+      "name": "batchStep6Failed",
+      "msg": "Bước 6 của batch thực thi thất bại"
+    },
+    {
+      "code": 6040,
+      "name": "batchStep7Failed",
+      "msg": "Bước 7 của batch thực thi thất bại"
+    },
+    {
+      "code": 6041,
+      "name": "batchStep8Failed",
+      "msg": "Bước 8 của batch thực thi thất bại"
+    },
+    {
+      "code": 6042,
+      "name": "batchStep9Failed",
+      "msg": "Bước 9 của batch thực thi thất bại"
+    },
+    {
+      "code": 6043,
+      "name": "proposalExpired",
+      "msg": "Đề xuất đã hết hạn"
+    },
+    {
+      "code": 6044,
+      "name": "invalidExpiry",
+      "msg": "Thời điểm hết hạn không hợp lệ"
+    },
+    {
+      "code": 6045,
+      "name": "proposalNotFinalized",
+      "msg": "Đề xuất chưa kết thúc, không thể đóng"
+    },
+    {
+      "code": 6046,
+      "name": "alreadyVoted",
+      "msg": "Guardian đã bỏ phiếu này cho đề xuất"
+    },
+    {
+      "code": 6047,
+      "name": "invalidTimelock",
+      "msg": "Cấu hình timelock không hợp lệ"
+    },
+    {
+      "code": 6048,
+      "name": "timelockActive",
+      "msg": "Đề xuất vẫn đang trong thời gian khóa"
+    },
+    {
+      "code": 6049,
+      "name": "spendingLimitExceeded",
+      "msg": "Vượt hạn mức chi tiêu, cần tạo đề xuất multisig"
+    },
+    {
+      "code": 6050,
+      "name": "destinationNotAllowed",
+      "msg": "Địa chỉ đích không nằm trong danh sách cho phép"
+    },
+    {
+      "code": 6051,
+      "name": "guardianChangeRequiresProposal",
+      "msg": "Thay đổi guardian phải được thực hiện qua đề xuất multisig"
+    },
+    {
+      "code": 6052,
+      "name": "noActiveOwner",
+      "msg": "Ví phải còn ít nhất một owner đang hoạt động"
+    },
+    {
+      "code": 6053,
+      "name": "staleRecoveryRequest",
+      "msg": "Yêu cầu khôi phục không còn hiệu lực"
+    },
+    {
+      "code": 6054,
+      "name": "recoveryDelayActive",
+      "msg": "Yêu cầu khôi phục vẫn đang trong thời gian thử thách"
+    },
+    {
+      "code": 6055,
+      "name": "recoveryChallengeEnded",
+      "msg": "Đã hết thời gian thử thách, không thể hủy yêu cầu khôi phục"
+    },
+    {
+      "code": 6056,
+      "name": "insufficientRecoveryApprovals",
+      "msg": "Chưa đủ guardian phê duyệt yêu cầu khôi phục"
+    },
+    {
+      "code": 6057,
+      "name": "recoveryNotFinalized",
+      "msg": "Yêu cầu khôi phục chưa kết thúc, không thể đóng"
+    },
+    {
+      "code": 6058,
+      "name": "noRecoveryApprovers",
+      "msg": "Ví không còn guardian nào có thể phê duyệt khôi phục"
+    },
+    {
+      "code": 6059,
+      "name": "recoveryRequestExpired",
+      "msg": "Yêu cầu khôi phục đã hết hạn"
+    },
+    {
+      "code": 6060,
+      "name": "invalidAuthenticatorData",
+      "msg": "authenticatorData không hợp lệ"
+    },
+    {
+      "code": 6061,
+      "name": "invalidClientData",
+      "msg": "clientDataJSON không hợp lệ"
+    },
+    {
+      "code": 6062,
+      "name": "challengeMismatch",
+      "msg": "Challenge trong clientDataJSON không khớp với hành động"
+    },
+    {
+      "code": 6063,
+      "name": "rpIdMismatch",
+      "msg": "rpIdHash không khớp với ví"
+    },
+    {
+      "code": 6064,
+      "name": "userNotVerified",
+      "msg": "Người dùng chưa được xác minh (cờ UP/UV)"
+    },
+    {
+      "code": 6065,
+      "name": "signCountReplay",
+      "msg": "Bộ đếm chữ ký không tăng, có thể là chữ ký bị phát lại"
+    },
+    {
+      "code": 6066,
+      "name": "invalidSecpInstructionIndex",
+      "msg": "Instruction secp256r1 tham chiếu dữ liệu ngoài chính nó"
+    },
+    {
+      "code": 6067,
+      "name": "unsupportedPayloadVersion",
+      "msg": "Phiên bản payload ký không được hỗ trợ"
+    },
+    {
+      "code": 6068,
+      "name": "guardianAuthMissing",
+      "msg": "Guardian chưa có khóa xác thực"
+    },
+    {
+      "code": 6069,
+      "name": "guardianAuthMismatch",
+      "msg": "Loại bằng chứng không khớp với loại khóa của guardian"
+    },
+    {
+      "code": 6070,
+      "name": "missingGuardianSignature",
+      "msg": "Guardian chưa ký giao dịch"
+    },
+    {
+      "code": 6071,
+      "name": "invalidDevice",
+      "msg": "Thiết bị không thuộc owner hoặc đã bị thu hồi"
+    },
+    {
+      "code": 6072,
+      "name": "devicesStillRegistered",
+      "msg": "Guardian vẫn còn thiết bị passkey, cần gỡ thiết bị trước"
+    },
+    {
+      "code": 6073,
+      "name": "permissionDenied",
+      "msg": "Guardian không có quyền thực hiện thao tác này"
+    },
+    {
+      "code": 6074,
+      "name": "pendingProposalsExist",
+      "msg": "Ví còn đề xuất đang chờ, cần thực thi, từ chối hoặc hủy trước khi chuyển"
+    },
+    {
+      "code": 6075,
+      "name": "recoveryInProgress",
+      "msg": "Ví đang có yêu cầu khôi phục chưa kết thúc"
+    },
+    {
+      "code": 6076,
+      "name": "accountAlreadyMigrated",
+      "msg": "Tài khoản đã ở layout mới nhất"
+    },
+    {
+      "code": 6077,
+      "name": "walletNotMigrated",
+      "msg": "Ví phải được chuyển lên layout mới trước các đề xuất của nó"
+    }
+  ],
+  "types": [
+    {
+      "name": "actionParams",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "amount",
+            "type": {
+              "option": "u64"
+            }
+          },
+          {
+            "name": "destination",
+            "type": {
+              "option": "pubkey"
+            }
+          },
+          {
+            "name": "tokenMint",
+            "type": {
+              "option": "pubkey"
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "configAction",
+      "docs": [
+        "Thay đổi cấu hình ví, chỉ được áp dụng qua đề xuất \"config\" đã đủ ngưỡng"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "setTimelock",
+            "fields": [
+              {
+                "defined": {
+                  "name": "timelockConfig"
+                }
+              }
+            ]
+          },
+          {
+            "name": "setSpendingPolicy",
+            "fields": [
+              {
+                "defined": {
+                  "name": "spendingPolicy"
+                }
+              }
+            ]
+          },
+          {
+            "name": "addGuardian",
+            "fields": [
+              {
+                "name": "guardianId",
+                "type": "u64"
+              },
+              {
+                "name": "name",
+                "type": "string"
+              },
+              {
+                "name": "recoveryHash",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              },
+              {
+                "name": "isOwner",
+                "type": "bool"
+              },
+              {
+                "name": "auth",
+                "type": {
+                  "defined": {
+                    "name": "guardianAuth"
+                  }
+                }
+              },
+              {
+                "name": "permissions",
+                "type": "u8"
+              },
+              {
+                "name": "weight",
+                "type": "u8"
+              }
+            ]
+          },
+          {
+            "name": "removeGuardian",
+            "fields": [
+              {
+                "name": "guardianId",
+                "type": "u64"
+              }
+            ]
+          },
+          {
+            "name": "setGuardianStatus",
+            "fields": [
+              {
+                "name": "guardianId",
+                "type": "u64"
+              },
+              {
+                "name": "isActive",
+                "type": "bool"
+              }
+            ]
+          },
+          {
+            "name": "setGuardianOwner",
+            "fields": [
+              {
+                "name": "guardianId",
+                "type": "u64"
+              },
+              {
+                "name": "isOwner",
+                "type": "bool"
+              }
+            ]
+          },
+          {
+            "name": "changeThreshold",
+            "fields": [
+              {
+                "name": "threshold",
+                "type": "u8"
+              }
+            ]
+          },
+          {
+            "name": "setRpIdHash",
+            "fields": [
+              {
+                "name": "rpIdHash",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              }
+            ]
+          },
+          {
+            "name": "setGuardianPermissions",
+            "fields": [
+              {
+                "name": "guardianId",
+                "type": "u64"
+              },
+              {
+                "name": "permissions",
+                "type": "u8"
+              }
+            ]
+          },
+          {
+            "name": "migrateSeeds",
+            "fields": [
+              {
+                "name": "salt",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              }
+            ]
+          },
+          {
+            "name": "setGuardianWeight",
+            "fields": [
+              {
+                "name": "guardianId",
+                "type": "u64"
+              },
+              {
+                "name": "weight",
+                "type": "u8"
+              }
+            ]
+          },
+          {
+            "name": "setWeightedVoting",
+            "fields": [
+              {
+                "name": "enabled",
+                "type": "bool"
+              }
+            ]
+          },
+          {
+            "name": "setGuardianSpendingLimits",
+            "fields": [
+              {
+                "name": "guardianId",
+                "type": "u64"
+              },
+              {
+                "name": "limits",
+                "type": {
+                  "vec": {
+                    "defined": {
+                      "name": "spendingLimit"
+                    }
+                  }
+                }
+              }
+            ]
+          }
+        ]
+      }
     },
     {
-      "code": 6014,
-      "name": "instructionMissing",
-      "msg": "Instruction xác thực chữ ký bị thiếu"
+      "name": "guardian",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "version",
+            "type": "u8"
+          },
+          {
+            "name": "wallet",
+            "type": "pubkey"
+          },
+          {
+            "name": "guardianId",
+            "type": "u64"
+          },
+          {
+            "name": "name",
+            "type": "string"
+          },
+          {
+            "name": "isActive",
+            "type": "bool"
+          },
+          {
+            "name": "recoveryHash",
+            "type": {
+              "array": [
+                "u8",
+                32
+              ]
+            }
+          },
+          {
+            "name": "isOwner",
+            "type": "bool"
+          },
+          {
+            "name": "auth",
+            "type": {
+              "option": {
+                "defined": {
+                  "name": "guardianAuth"
+                }
+              }
+            }
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "signCount",
+            "type": "u32"
+          },
+          {
+            "name": "nonce",
+            "type": "u64"
+          },
+          {
+            "name": "deviceCount",
+            "type": "u8"
+          },
+          {
+            "name": "deviceEpoch",
+            "type": "u32"
+          },
+          {
+            "name": "permissions",
+            "type": "u8"
+          },
+          {
+            "name": "spendingLimits",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "spendingLimit"
+                }
+              }
+            }
+          },
+          {
+            "name": "weight",
+            "type": "u8"
+          },
+          {
+            "name": "reserved",
+            "type": {
+              "array": [
+                "u8",
+                31
+              ]
+            }
+          }
+        ]
+      }
     },
     {
-      "code": 6015,
-      "name": "invalidSignatureVerification",
-      "msg": "Xác thực chữ ký không hợp lệ"
+      "name": "guardianApproval",
+      "docs": [
+        "Một phê duyệt trong `batch_approve`, mỗi guardian ký challenge của riêng mình"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "guardianId",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          },
+          {
+            "name": "proof",
+            "type": {
+              "defined": {
+                "name": "guardianProof"
+              }
+            }
+          }
+        ]
+      }
     },
     {
-      "code": 6016,
-      "name": "publicKeyMismatch",
-      "msg": "Public key không khớp với wallet"
+      "name": "guardianAuth",
+      "docs": [
+        "Cách guardian chứng minh danh tính khi phê duyệt"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "passkey",
+            "fields": [
+              {
+                "array": [
+                  "u8",
+                  33
+                ]
+              }
+            ]
+          },
+          {
+            "name": "ed25519",
+            "fields": [
+              {
+                "array": [
+                  "u8",
+                  32
+                ]
+              }
+            ]
+          },
+          {
+            "name": "signer",
+            "fields": [
+              "pubkey"
+            ]
+          }
+        ]
+      }
     },
     {
-      "code": 6017,
-      "name": "messageMismatch",
-      "msg": "Message không khớp"
+      "name": "guardianEntry",
+      "docs": [
+        "Bản tóm tắt một guardian trong danh sách của ví, để client liệt kê guardian mà không cần quét PDA"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "guardianId",
+            "type": "u64"
+          },
+          {
+            "name": "permissions",
+            "type": "u8"
+          },
+          {
+            "name": "isActive",
+            "type": "bool"
+          },
+          {
+            "name": "isOwner",
+            "type": "bool"
+          }
+        ]
+      }
     },
     {
-      "code": 6018,
-      "name": "invalidInstructionData",
-      "msg": "Dữ liệu instruction không hợp lệ"
+      "name": "guardianProof",
+      "docs": [
+        "Bằng chứng guardian gửi kèm khi ký, phải cùng loại với `Guardian::auth`"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "passkey",
+            "fields": [
+              {
+                "defined": {
+                  "name": "webAuthnAssertion"
+                }
+              }
+            ]
+          },
+          {
+            "name": "ed25519"
+          },
+          {
+            "name": "signer"
+          }
+        ]
+      }
     },
     {
-      "code": 6019,
-      "name": "invalidSignatureCount",
-      "msg": "Số lượng chữ ký không hợp lệ"
+      "name": "multiSigWallet",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "version",
+            "type": "u8"
+          },
+          {
+            "name": "threshold",
+            "type": "u8"
+          },
+          {
+            "name": "guardianCount",
+            "type": "u8"
+          },
+          {
+            "name": "activeGuardianCount",
+            "type": "u8"
+          },
+          {
+            "name": "ownerCount",
+            "type": "u8"
+          },
+          {
+            "name": "approverCount",
+            "type": "u8"
+          },
+          {
+            "name": "recoveryNonce",
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "transactionNonce",
+            "type": "u64"
+          },
+          {
+            "name": "lastTransactionTimestamp",
+            "type": "i64"
+          },
+          {
+            "name": "owner",
+            "type": "pubkey"
+          },
+          {
+            "name": "credentialId",
+            "type": "string"
+          },
+          {
+            "name": "timelock",
+            "type": {
+              "defined": {
+                "name": "timelockConfig"
+              }
+            }
+          },
+          {
+            "name": "spendingPolicy",
+            "type": {
+              "defined": {
+                "name": "spendingPolicy"
+              }
+            }
+          },
+          {
+            "name": "rpIdHash",
+            "type": {
+              "array": [
+                "u8",
+                32
+              ]
+            }
+          },
+          {
+            "name": "seedVersion",
+            "type": "u8"
+          },
+          {
+            "name": "salt",
+            "type": {
+              "array": [
+                "u8",
+                32
+              ]
+            }
+          },
+          {
+            "name": "migratedFrom",
+            "type": {
+              "option": "pubkey"
+            }
+          },
+          {
+            "name": "pendingProposals",
+            "type": "u16"
+          },
+          {
+            "name": "guardians",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "guardianEntry"
+                }
+              }
+            }
+          },
+          {
+            "name": "proposalCount",
+            "type": "u64"
+          },
+          {
+            "name": "weightedVoting",
+            "type": "bool"
+          },
+          {
+            "name": "approverWeight",
+            "type": "u16"
+          },
+          {
+            "name": "reserved",
+            "type": {
+              "array": [
+                "u8",
+                61
+              ]
+            }
+          }
+        ]
+      }
     },
     {
-      "code": 6020,
-      "name": "unsupportedAction",
-      "msg": "Hành động không được hỗ trợ"
+      "name": "ownerDevice",
+      "docs": [
+        "Một passkey bổ sung của owner (điện thoại, laptop, khóa bảo mật...).",
+        "PDA: [b\"device\", guardian, SHA256(credential_id)]"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "version",
+            "type": "u8"
+          },
+          {
+            "name": "guardian",
+            "type": "pubkey"
+          },
+          {
+            "name": "credentialId",
+            "type": "string"
+          },
+          {
+            "name": "credentialHash",
+            "type": {
+              "array": [
+                "u8",
+                32
+              ]
+            }
+          },
+          {
+            "name": "pubkey",
+            "type": {
+              "array": [
+                "u8",
+                33
+              ]
+            }
+          },
+          {
+            "name": "label",
+            "type": "string"
+          },
+          {
+            "name": "createdAt",
+            "type": "i64"
+          },
+          {
+            "name": "signCount",
+            "type": "u32"
+          },
+          {
+            "name": "epoch",
+            "type": "u32"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "reserved",
+            "type": {
+              "array": [
+                "u8",
+                16
+              ]
+            }
+          }
+        ]
+      }
     },
     {
-      "code": 6021,
-      "name": "invalidGuardian",
-      "msg": "Guardian không hợp lệ hoặc không được tìm thấy"
+      "name": "proposalAccountMeta",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pubkey",
+            "type": "pubkey"
+          },
+          {
+            "name": "isSigner",
+            "type": "bool"
+          },
+          {
+            "name": "isWritable",
+            "type": "bool"
+          }
+        ]
+      }
     },
     {
-      "code": 6022,
-      "name": "inactiveGuardian",
-      "msg": "Guardian đang không hoạt động"
+      "name": "proposalAction",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "action",
+            "type": "string"
+          },
+          {
+            "name": "params",
+            "type": {
+              "defined": {
+                "name": "actionParams"
+              }
+            }
+          }
+        ]
+      }
     },
     {
-      "code": 6023,
-      "name": "arithmeticOverflow",
-      "msg": "Lỗi tràn số trong phép tính"
+      "name": "proposalInstruction",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "programId",
+            "type": "pubkey"
+          },
+          {
+            "name": "accounts",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "proposalAccountMeta"
+                }
+              }
+            }
+          },
+          {
+            "name": "data",
+            "type": "bytes"
+          }
+        ]
+      }
     },
     {
-      "code": 6024,
-      "name": "insufficientFunds",
-      "msg": "Không đủ SOL trong tài khoản"
+      "name": "proposalSignature",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "version",
+            "type": "u8"
+          },
+          {
+            "name": "proposal",
+            "type": "pubkey"
+          },
+          {
+            "name": "guardianId",
+            "type": "u64"
+          },
+          {
+            "name": "signatureTime",
+            "type": "i64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "approved",
+            "type": "bool"
+          },
+          {
+            "name": "weight",
+            "type": "u8"
+          },
+          {
+            "name": "reserved",
+            "type": {
+              "array": [
+                "u8",
+                15
+              ]
+            }
+          }
+        ]
+      }
     },
     {
-      "code": 6025,
-      "name": "multisigMismatch",
-      "msg": "Địa chỉ ví multisig không khớp với đề xuất"
+      "name": "proposalStatus",
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "pending"
+          },
+          {
+            "name": "executed"
+          },
+          {
+            "name": "rejected"
+          },
+          {
+            "name": "expired"
+          },
+          {
+            "name": "cancelled"
+          }
+        ]
+      }
     },
     {
-      "code": 6026,
-      "name": "invalidOwner",
-      "msg": "Chủ sở hữu tài khoản không hợp lệ"
-    }
-  ],
-  "types": [
-    {
-      "name": "actionParams",
+      "name": "recoveryApproval",
       "type": {
         "kind": "struct",
         "fields": [
           {
-            "name": "amount",
-            "type": {
-              "option": "u64"
-            }
+            "name": "version",
+            "type": "u8"
           },
           {
-            "name": "destination",
-            "type": {
-              "option": "pubkey"
-            }
+            "name": "request",
+            "type": "pubkey"
           },
           {
-            "name": "tokenMint",
+            "name": "guardianId",
+            "type": "u64"
+          },
+          {
+            "name": "approvedAt",
+            "type": "i64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "payer",
+            "type": "pubkey"
+          },
+          {
+            "name": "reserved",
             "type": {
-              "option": "pubkey"
+              "array": [
+                "u8",
+                16
+              ]
             }
           }
         ]
       }
     },
     {
-      "name": "guardian",
+      "name": "recoveryRequest",
       "type": {
         "kind": "struct",
         "fields": [
           {
-            "name": "wallet",
+            "name": "version",
+            "type": "u8"
+          },
+          {
+            "name": "multisig",
             "type": "pubkey"
           },
           {
-            "name": "guardianId",
+            "name": "recoveryNonce",
             "type": "u64"
           },
           {
-            "name": "name",
-            "type": "string"
+            "name": "oldGuardianId",
+            "type": "u64"
           },
           {
-            "name": "isActive",
-            "type": "bool"
+            "name": "newGuardianId",
+            "type": "u64"
           },
           {
-            "name": "recoveryHash",
+            "name": "newWebauthnPubkey",
+            "type": {
+              "array": [
+                "u8",
+                33
+              ]
+            }
+          },
+          {
+            "name": "newRecoveryHash",
             "type": {
               "array": [
                 "u8",
@@ -820,116 +2669,198 @@ export type MoonWalletProgram = {
             }
           },
           {
-            "name": "isOwner",
-            "type": "bool"
+            "name": "createdAt",
+            "type": "i64"
+          },
+          {
+            "name": "executableAt",
+            "type": "i64"
+          },
+          {
+            "name": "expiresAt",
+            "type": "i64"
+          },
+          {
+            "name": "approvalsCount",
+            "type": "u8"
+          },
+          {
+            "name": "requiredApprovals",
+            "type": "u8"
           },
           {
-            "name": "webauthnPubkey",
+            "name": "status",
             "type": {
-              "option": {
-                "array": [
-                  "u8",
-                  33
-                ]
+              "defined": {
+                "name": "recoveryStatus"
               }
             }
           },
           {
             "name": "bump",
             "type": "u8"
+          },
+          {
+            "name": "weighted",
+            "type": "bool"
+          },
+          {
+            "name": "approvalWeight",
+            "type": "u16"
+          },
+          {
+            "name": "reserved",
+            "type": {
+              "array": [
+                "u8",
+                13
+              ]
+            }
           }
         ]
       }
     },
     {
-      "name": "multiSigWallet",
+      "name": "recoveryStatus",
       "type": {
-        "kind": "struct",
-        "fields": [
+        "kind": "enum",
+        "variants": [
           {
-            "name": "threshold",
-            "type": "u8"
+            "name": "pending"
           },
           {
-            "name": "guardianCount",
-            "type": "u8"
+            "name": "executed"
           },
           {
-            "name": "recoveryNonce",
-            "type": "u64"
+            "name": "cancelled"
+          }
+        ]
+      }
+    },
+    {
+      "name": "spendingLimit",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "mint",
+            "type": {
+              "option": "pubkey"
+            }
           },
           {
-            "name": "bump",
-            "type": "u8"
+            "name": "perTransactionCap",
+            "type": "u64"
           },
           {
-            "name": "transactionNonce",
+            "name": "allowance",
             "type": "u64"
           },
           {
-            "name": "lastTransactionTimestamp",
+            "name": "period",
             "type": "i64"
           },
           {
-            "name": "owner",
-            "type": "pubkey"
+            "name": "spent",
+            "type": "u64"
           },
           {
-            "name": "credentialId",
-            "type": "string"
+            "name": "updatedAt",
+            "type": "i64"
           }
         ]
       }
     },
     {
-      "name": "proposalSignature",
+      "name": "spendingPolicy",
+      "docs": [
+        "Chính sách chi tiêu cho verify_and_execute. Ví mới bắt đầu với `SpendingPolicy::restricted`;",
+        "giao dịch vượt hạn mức (hoặc mint chưa có hạn mức) phải đi qua đề xuất multisig.",
+        "Khi chính sách tắt (`enabled = false`), guardian có PERMISSION_SPEND không thể chi trực tiếp.",
+        "`limits` là trần chung của cả ví; mỗi guardian còn bị giới hạn bởi `Guardian::spending_limits` của mình."
+      ],
       "type": {
         "kind": "struct",
         "fields": [
           {
-            "name": "proposal",
-            "type": "pubkey"
+            "name": "enabled",
+            "type": "bool"
           },
           {
-            "name": "guardianId",
-            "type": "u64"
+            "name": "limits",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "spendingLimit"
+                }
+              }
+            }
           },
           {
-            "name": "signatureTime",
+            "name": "allowlist",
+            "type": {
+              "vec": "pubkey"
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "timelockConfig",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "delay",
             "type": "i64"
           },
           {
-            "name": "bump",
-            "type": "u8"
+            "name": "tiers",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "timelockTier"
+                }
+              }
+            }
           }
         ]
       }
     },
     {
-      "name": "proposalStatus",
+      "name": "timelockTier",
       "type": {
-        "kind": "enum",
-        "variants": [
-          {
-            "name": "pending"
-          },
+        "kind": "struct",
+        "fields": [
           {
-            "name": "executed"
+            "name": "mint",
+            "type": {
+              "option": "pubkey"
+            }
           },
           {
-            "name": "rejected"
+            "name": "minAmount",
+            "type": "u64"
           },
           {
-            "name": "expired"
+            "name": "delay",
+            "type": "i64"
           }
         ]
       }
     },
     {
       "name": "transactionProposal",
+      "docs": [
+        "Kích thước phụ thuộc nội dung nên không dùng InitSpace, xem `TransactionProposal::space_for`"
+      ],
       "type": {
         "kind": "struct",
         "fields": [
+          {
+            "name": "version",
+            "type": "u8"
+          },
           {
             "name": "multisig",
             "type": "pubkey"
@@ -980,13 +2911,120 @@ export type MoonWalletProgram = {
             "name": "signaturesCount",
             "type": "u8"
           },
+          {
+            "name": "rejectionsCount",
+            "type": "u8"
+          },
           {
             "name": "requiredSignatures",
             "type": "u8"
           },
+          {
+            "name": "approverCount",
+            "type": "u8"
+          },
           {
             "name": "bump",
             "type": "u8"
+          },
+          {
+            "name": "instructions",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "proposalInstruction"
+                }
+              }
+            }
+          },
+          {
+            "name": "actions",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "proposalAction"
+                }
+              }
+            }
+          },
+          {
+            "name": "expiresAt",
+            "type": {
+              "option": "i64"
+            }
+          },
+          {
+            "name": "configAction",
+            "type": {
+              "option": {
+                "defined": {
+                  "name": "configAction"
+                }
+              }
+            }
+          },
+          {
+            "name": "weighted",
+            "type": "bool"
+          },
+          {
+            "name": "approvalWeight",
+            "type": "u16"
+          },
+          {
+            "name": "rejectionWeight",
+            "type": "u16"
+          },
+          {
+            "name": "totalWeight",
+            "type": "u16"
+          },
+          {
+            "name": "reserved",
+            "type": {
+              "array": [
+                "u8",
+                25
+              ]
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "walletMigrationParams",
+      "docs": [
+        "Thông tin ví không có trong layout cũ, do owner cung cấp khi chuyển tài khoản ví"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "rpId",
+            "type": "string"
+          }
+        ]
+      }
+    },
+    {
+      "name": "webAuthnAssertion",
+      "docs": [
+        "Kết quả `navigator.credentials.get()` gửi lên nguyên trạng từ trình duyệt"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "payloadVersion",
+            "type": "u8"
+          },
+          {
+            "name": "authenticatorData",
+            "type": "bytes"
+          },
+          {
+            "name": "clientDataJson",
+            "type": "bytes"
           }
         ]
       }